@@ -16,7 +16,6 @@
 // max vertex buffer array stride is 2k
 
 // good tutuorials for graphs: https://en.wikibooks.org/wiki/OpenGL_Programming
-//use crate::compute::Compute;
 //use crate::instance_shader::InstanceShader;
 use anyhow::Result;
 use cgmath::{ortho, vec2, InnerSpace, Matrix4, Ortho, Rotation3, Vector2, Zero};
@@ -75,7 +74,6 @@ use winit::{
     window::WindowBuilder,
 };
 
-//mod compute;
 //mod line_shader;
 //mod lion;
 mod bug;
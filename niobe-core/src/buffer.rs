@@ -1,164 +1,415 @@
 use bytemuck::Pod;
 use num_traits::{CheckedAdd, One, Zero};
 use std::collections::Bound;
+use std::iter;
+use std::marker::PhantomData;
 use std::mem;
-use std::ops::{Deref, Index, Range, RangeBounds};
-use wgpu::util::{BufferInitDescriptor, DeviceExt};
-use wgpu::{BufferAddress, BufferUsages, Device, Queue};
-
-//#[derive(Debug)]
-//pub struct Buffer<T: Copy + Pod> {
-//    pub buf: wgpu::Buffer,
-//    len: u32,
-//    phantom_data: std::marker::PhantomData<T>,
-//}
-//
-//impl<T: Copy + Pod> Buffer<T> {
-//    pub fn new(device: &Device, usage: BufferUsages, data: &[T]) -> Self {
-//        let contents = bytemuck::cast_slice(data);
-//        debug_assert!(data.len() <= u32::MAX as usize);
-//
-//        Self {
-//            buf: device.create_buffer_init(&BufferInitDescriptor {
-//                label: None,
-//                contents,
-//                usage,
-//            }),
-//            len: data.len() as u32,
-//            phantom_data: std::marker::PhantomData,
-//        }
-//    }
-//
-//    pub fn slice(&self, range: impl RangeBounds<u32>) -> BufferSlice<'_, T> {
-//        let start = match range.start_bound() {
-//            Bound::Included(b) => *b,
-//            Bound::Excluded(_) => unreachable!("range start bound cannot be excluded"),
-//            Bound::Unbounded => 0,
-//        };
-//        let end = match range.end_bound() {
-//            Bound::Included(b) => *b + 1,
-//            Bound::Excluded(b) => *b,
-//            Bound::Unbounded => self.len,
-//        };
-//        BufferSlice {
-//            buf: &self.buf,
-//            range: start..end,
-//            phantom_data: Default::default(),
-//        }
-//    }
-//
-//    pub fn len(&self) -> u32 {
-//        self.len
-//    }
-//
-//    pub fn write(&self, queue: &Queue, offset: usize, data: &[T]) {
-//        queue.write_buffer(
-//            &self.buf,
-//            (offset * mem::size_of::<T>()) as BufferAddress,
-//            bytemuck::cast_slice(data),
-//        )
-//    }
-//
-//    pub fn write_sliced(&self, queue: &Queue, range: impl RangeBounds<usize>, data: &[T]) {
-//        let range = range_from_range_bounds(range, data.len());
-//        queue.write_buffer(
-//            &self.buf,
-//            (range.start * mem::size_of::<T>()) as BufferAddress,
-//            bytemuck::cast_slice(&data[range]),
-//        )
-//    }
-//}
-//
-////pub struct DynamicBuffer<T: Copy + Pod>(Buffer<T>);
-////
-////impl<T: Copy + Pod> DynamicBuffer<T> {
-////    pub fn new(device: &wgpu::Device, len: usize, usage: wgpu::BufferUsage) -> Self {
-////        let buffer = Buffer {
-////            buf: device.create_buffer(&wgpu::BufferDescriptor {
-////                label: None,
-////                mapped_at_creation: false,
-////                size: len as u64 * std::mem::size_of::<T>() as u64,
-////                usage: usage | wgpu::BufferUsage::COPY_DST,
-////            }),
-////            len,
-////            phantom_data: std::marker::PhantomData,
-////        };
-////        Self(buffer)
-////    }
-////
-////    pub fn update(&self, queue: &wgpu::Queue, vals: &[T], offset: usize) {
-////        if !vals.is_empty() {
-////            queue.write_buffer(
-////                &self.buf,
-////                offset as u64 * std::mem::size_of::<T>() as u64,
-////                bytemuck::cast_slice(vals),
-////            )
-////        }
-////    }
-////}
-////
-////impl<T: Copy + Pod> std::ops::Deref for DynamicBuffer<T> {
-////    type Target = Buffer<T>;
-////
-////    fn deref(&self) -> &Self::Target { &self.0 }
-////}
-//
-//#[derive(Clone)]
-//pub struct BufferSlice<'a, T: Copy + Pod> {
-//    buf: &'a wgpu::Buffer,
-//    range: Range<u32>,
-//    phantom_data: std::marker::PhantomData<T>,
-//}
-//
-//impl<'a, T: Copy + Pod> BufferSlice<'a, T> {
-//    pub fn raw_addres_range(&self) -> Range<BufferAddress> {
-//        let size = mem::size_of::<T>() as BufferAddress;
-//        self.range.start as BufferAddress * size..self.range.end as BufferAddress * size
-//    }
-//
-//    pub fn range(&self) -> Range<u32> {
-//        self.range.clone()
-//    }
-//
-//    pub fn buf(&self) -> &'a wgpu::Buffer {
-//        self.buf
-//    }
-//
-//    pub fn to_raw_slice(&self) -> wgpu::BufferSlice<'a> {
-//        self.buf.slice(self.raw_addres_range())
-//    }
-//
-//    pub fn len(&self) -> u32 {
-//        self.range.end - self.range.start
-//    }
-//}
-//
-//fn range_from_range_bounds<T: One + CheckedAdd + Zero + Copy>(
-//    bounds: impl RangeBounds<T>,
-//    max_bound: T,
-//) -> Range<T> {
-//    let start = match bounds.start_bound() {
-//        Bound::Included(b) => *b,
-//        Bound::Excluded(_) => unreachable!("range start bound cannot be excluded"),
-//        Bound::Unbounded => T::zero(),
-//    };
-//    let end = match bounds.end_bound() {
-//        Bound::Included(b) => b.checked_add(&T::one()).unwrap(),
-//        Bound::Excluded(b) => *b,
-//        Bound::Unbounded => max_bound,
-//    };
-//    start..end
-//}
-
-pub struct Buffer {
+use std::ops::{Deref, Range, RangeBounds};
+use wgpu::util::{BufferInitDescriptor, DeviceExt, StagingBelt};
+use wgpu::{BufferAddress, BufferSize, BufferUsages, CommandEncoder, Device, Queue};
+
+/// Typed gpu buffer that keeps track of how many elements are initialized.
+/// Buffer can grow, when that happens the old contents are copied on the gpu into a new buffer
+/// and bind groups that reference it need to be recreated.
+#[derive(Debug)]
+pub struct Buffer<T: Pod> {
+    buf: wgpu::Buffer,
+    len: u32,
+    capacity: u32,
+    usage: BufferUsages,
+    phantom_data: PhantomData<T>,
+}
+
+impl<T: Pod> Buffer<T> {
+    /// Creates a buffer with `data`, it can be written to and grown like one from `with_capacity`.
+    pub fn new(device: &Device, usage: BufferUsages, data: &[T]) -> Self {
+        let contents = bytemuck::cast_slice(data);
+        debug_assert!(data.len() <= u32::MAX as usize);
+        let usage = usage | BufferUsages::COPY_DST | BufferUsages::COPY_SRC;
+
+        Self {
+            buf: device.create_buffer_init(&BufferInitDescriptor {
+                label: None,
+                contents,
+                usage,
+            }),
+            len: data.len() as u32,
+            capacity: data.len() as u32,
+            usage,
+            phantom_data: PhantomData,
+        }
+    }
+
+    /// Creates an empty buffer that can be appended to.
+    pub fn with_capacity(device: &Device, usage: BufferUsages, capacity: u32) -> Self {
+        let usage = usage | BufferUsages::COPY_DST | BufferUsages::COPY_SRC;
+        Self {
+            buf: create_buffer::<T>(device, usage, capacity),
+            len: 0,
+            capacity,
+            usage,
+            phantom_data: PhantomData,
+        }
+    }
+
+    pub fn slice(&self, range: impl RangeBounds<u32>) -> BufferSlice<'_, T> {
+        BufferSlice {
+            buf: &self.buf,
+            range: range_from_range_bounds(range, self.len),
+            phantom_data: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    pub fn usage(&self) -> BufferUsages {
+        self.usage
+    }
+
+    pub fn buf(&self) -> &wgpu::Buffer {
+        &self.buf
+    }
+
+    /// Overwrites elements starting at `offset`, written range must be within `len`.
+    pub fn write(&self, queue: &Queue, offset: usize, data: &[T]) {
+        debug_assert!(offset + data.len() <= self.len as usize);
+        queue.write_buffer(
+            &self.buf,
+            (offset * mem::size_of::<T>()) as BufferAddress,
+            bytemuck::cast_slice(data),
+        )
+    }
+
+    /// Writes `data[range]` to the same range in the buffer.
+    pub fn write_sliced(&self, queue: &Queue, range: impl RangeBounds<usize>, data: &[T]) {
+        let range = range_from_range_bounds(range, data.len());
+        debug_assert!(range.end <= self.len as usize);
+        queue.write_buffer(
+            &self.buf,
+            (range.start * mem::size_of::<T>()) as BufferAddress,
+            bytemuck::cast_slice(&data[range]),
+        )
+    }
+
+    /// Appends `data` at the end of the buffer, growing it if needed.
+    /// Returns true if the buffer was reallocated.
+    pub fn extend(&mut self, device: &Device, queue: &Queue, data: &[T]) -> bool {
+        if data.is_empty() {
+            return false;
+        }
+        let new_len = self.len as usize + data.len();
+        debug_assert!(new_len <= u32::MAX as usize);
+        let reallocated = self.reserve(device, queue, data.len() as u32);
+        queue.write_buffer(
+            &self.buf,
+            (self.len as usize * mem::size_of::<T>()) as BufferAddress,
+            bytemuck::cast_slice(data),
+        );
+        self.len = new_len as u32;
+        reallocated
+    }
+
+    /// Appends one element, see [`Buffer::extend`].
+    pub fn push(&mut self, device: &Device, queue: &Queue, value: T) -> bool {
+        self.extend(device, queue, &[value])
+    }
+
+    /// Makes sure that at least `additional` elements can be appended without reallocating.
+    /// Capacity is at least doubled to amortize the cost of reallocations.
+    /// Returns true if the buffer was reallocated.
+    pub fn reserve(&mut self, device: &Device, queue: &Queue, additional: u32) -> bool {
+        let required = self
+            .len
+            .checked_add(additional)
+            .expect("buffer capacity overflow");
+        if required <= self.capacity {
+            return false;
+        }
+        let capacity = required.max(self.capacity.saturating_mul(2));
+        self.usage |= BufferUsages::COPY_DST | BufferUsages::COPY_SRC;
+        let buf = create_buffer::<T>(device, self.usage, capacity);
+        if self.len != 0 {
+            // queue writes are executed before submitted command buffers, copied range never
+            // overlaps with appended data
+            let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("niobe-core::Buffer::reserve"),
+            });
+            encoder.copy_buffer_to_buffer(
+                &self.buf,
+                0,
+                &buf,
+                0,
+                self.len as BufferAddress * mem::size_of::<T>() as BufferAddress,
+            );
+            queue.submit(iter::once(encoder.finish()));
+        }
+        self.buf = buf;
+        self.capacity = capacity;
+        true
+    }
+
+    /// Shortens the buffer, capacity stays the same.
+    pub fn truncate(&mut self, len: u32) {
+        self.len = self.len.min(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+impl<T: Pod> Deref for Buffer<T> {
+    type Target = wgpu::Buffer;
+
+    fn deref(&self) -> &Self::Target {
+        &self.buf
+    }
+}
+
+#[derive(Clone)]
+pub struct BufferSlice<'a, T: Pod> {
+    buf: &'a wgpu::Buffer,
+    range: Range<u32>,
+    phantom_data: PhantomData<T>,
+}
+
+impl<'a, T: Pod> BufferSlice<'a, T> {
+    pub fn raw_addres_range(&self) -> Range<BufferAddress> {
+        let size = mem::size_of::<T>() as BufferAddress;
+        self.range.start as BufferAddress * size..self.range.end as BufferAddress * size
+    }
+
+    pub fn range(&self) -> Range<u32> {
+        self.range.clone()
+    }
+
+    pub fn buf(&self) -> &'a wgpu::Buffer {
+        self.buf
+    }
+
+    pub fn to_raw_slice(&self) -> wgpu::BufferSlice<'a> {
+        self.buf.slice(self.raw_addres_range())
+    }
+
+    pub fn len(&self) -> u32 {
+        self.range.end - self.range.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.range.start == self.range.end
+    }
+
+    /// Slices further, `range` is relative to the start of this slice.
+    pub fn slice(&self, range: impl RangeBounds<u32>) -> BufferSlice<'a, T> {
+        let range = range_from_range_bounds(range, self.len());
+        debug_assert!(range.end <= self.len());
+        BufferSlice {
+            buf: self.buf,
+            range: self.range.start + range.start..self.range.start + range.end,
+            phantom_data: PhantomData,
+        }
+    }
+}
+
+/// Fixed capacity gpu buffer, once full the oldest elements get overwritten.
+/// Used to stream data without reuploading everything that was written before.
+///
+/// There is one more slot than capacity which mirrors the first element, that way line strips
+/// stay connected across the wrap around point, see [`RingBuffer::strip_slices`].
+pub struct RingBuffer<T: Pod> {
+    buf: wgpu::Buffer,
+    capacity: u32,
+    len: u32,
+    /// index of the next element to be written
+    head: u32,
+    phantom_data: PhantomData<T>,
+}
+
+impl<T: Pod> RingBuffer<T> {
+    pub fn new(device: &Device, usage: BufferUsages, capacity: u32) -> Self {
+        debug_assert!(capacity > 0);
+        debug_assert_eq!(
+            mem::size_of::<T>() as BufferAddress % wgpu::COPY_BUFFER_ALIGNMENT,
+            0
+        );
+        Self {
+            buf: create_buffer::<T>(device, usage | BufferUsages::COPY_DST, capacity + 1),
+            capacity,
+            len: 0,
+            head: 0,
+            phantom_data: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> u32 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.capacity
+    }
+
+    pub fn capacity(&self) -> u32 {
+        self.capacity
+    }
+
+    /// Index of the oldest element.
+    pub fn tail(&self) -> u32 {
+        if self.is_full() {
+            self.head
+        } else {
+            0
+        }
+    }
+
+    pub fn buf(&self) -> &wgpu::Buffer {
+        &self.buf
+    }
+
+    pub fn push(
+        &mut self,
+        belt: &mut StagingBelt,
+        encoder: &mut CommandEncoder,
+        device: &Device,
+        value: T,
+    ) {
+        self.extend(belt, encoder, device, &[value]);
+    }
+
+    /// Appends `data` through the staging belt, only written elements are uploaded.
+    /// If there is more data than capacity only the last `capacity` elements are kept.
+    pub fn extend(
+        &mut self,
+        belt: &mut StagingBelt,
+        encoder: &mut CommandEncoder,
+        device: &Device,
+        mut data: &[T],
+    ) {
+        if data.len() > self.capacity as usize {
+            let skip = data.len() - self.capacity as usize;
+            self.head = ((self.head as usize + skip) % self.capacity as usize) as u32;
+            data = &data[skip..];
+        }
+        while !data.is_empty() {
+            let n = data.len().min((self.capacity - self.head) as usize);
+            self.write(belt, encoder, device, self.head, &data[..n]);
+            if self.head == 0 {
+                self.write(belt, encoder, device, self.capacity, &data[..1]);
+            }
+            self.head = (self.head + n as u32) % self.capacity;
+            self.len = (self.len + n as u32).min(self.capacity);
+            data = &data[n..];
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.head = 0;
+    }
+
+    /// Returns contents from oldest to newest, second slice is only non empty when contents wrap
+    /// around.
+    pub fn slices(&self) -> (BufferSlice<'_, T>, BufferSlice<'_, T>) {
+        let tail = self.tail();
+        if tail == 0 {
+            (self.slice(0..self.len), self.slice(0..0))
+        } else {
+            (self.slice(tail..self.capacity), self.slice(0..self.head))
+        }
+    }
+
+    /// Same as [`RingBuffer::slices`] but first slice also contains the mirrored first element,
+    /// so that a line strip drawn from both slices has no gap at the wrap around point.
+    pub fn strip_slices(&self) -> (BufferSlice<'_, T>, BufferSlice<'_, T>) {
+        let tail = self.tail();
+        if tail == 0 {
+            (self.slice(0..self.len), self.slice(0..0))
+        } else {
+            (
+                self.slice(tail..self.capacity + 1),
+                self.slice(0..self.head),
+            )
+        }
+    }
+
+    fn slice(&self, range: Range<u32>) -> BufferSlice<'_, T> {
+        BufferSlice {
+            buf: &self.buf,
+            range,
+            phantom_data: PhantomData,
+        }
+    }
+
+    fn write(
+        &self,
+        belt: &mut StagingBelt,
+        encoder: &mut CommandEncoder,
+        device: &Device,
+        offset: u32,
+        data: &[T],
+    ) {
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        belt.write_buffer(
+            encoder,
+            &self.buf,
+            offset as BufferAddress * mem::size_of::<T>() as BufferAddress,
+            BufferSize::new(bytes.len() as BufferAddress).unwrap(),
+            device,
+        )
+        .copy_from_slice(bytes);
+    }
+}
+
+/// Untyped buffer, used when buffers of different types need to be stored together.
+pub struct RawBuffer {
     pub len: u32,
     pub buffer: wgpu::Buffer,
 }
 
-impl Deref for Buffer {
+impl Deref for RawBuffer {
     type Target = wgpu::Buffer;
 
     fn deref(&self) -> &Self::Target {
         &self.buffer
     }
 }
+
+fn create_buffer<T: Pod>(device: &Device, usage: BufferUsages, capacity: u32) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: capacity as BufferAddress * mem::size_of::<T>() as BufferAddress,
+        usage,
+        mapped_at_creation: false,
+    })
+}
+
+fn range_from_range_bounds<T: One + CheckedAdd + Zero + Copy>(
+    bounds: impl RangeBounds<T>,
+    max_bound: T,
+) -> Range<T> {
+    let start = match bounds.start_bound() {
+        Bound::Included(b) => *b,
+        Bound::Excluded(_) => unreachable!("range start bound cannot be excluded"),
+        Bound::Unbounded => T::zero(),
+    };
+    let end = match bounds.end_bound() {
+        Bound::Included(b) => b.checked_add(&T::one()).unwrap(),
+        Bound::Excluded(b) => *b,
+        Bound::Unbounded => max_bound,
+    };
+    start..end
+}
@@ -1,4 +1,12 @@
 use rgb::{RGB, RGBA};
+
+pub mod colormap;
+pub mod convert;
+pub mod parse;
+
+pub use convert::{color32_to_wgpu, from_color32, ColorExt};
+pub use parse::{color_by_name, parse_color, parse_color32, ParseColorError};
+
 // TODO: find color library with constants defined
 // code modified from
 
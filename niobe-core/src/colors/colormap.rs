@@ -0,0 +1,372 @@
+use rgb::RGBA;
+use std::num::NonZeroU32;
+use wgpu::{Device, Queue, Texture};
+
+/// Number of colors that polynomial maps are evaluated at, same as matplotlib tables.
+const TABLE_SIZE: usize = 256;
+
+/// Maps a scalar in `0..=1` to a color.
+/// Continuous maps interpolate linearly between evenly spaced colors, categorical maps split the
+/// range into a bin per color.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Colormap {
+    colors: Vec<RGBA<f32>>,
+    categorical: bool,
+}
+
+impl Colormap {
+    /// Continuous map through evenly spaced `colors`, there must be at least one.
+    pub fn new(colors: Vec<RGBA<f32>>) -> Self {
+        assert!(!colors.is_empty(), "colormap needs at least one color");
+        Self {
+            colors,
+            categorical: false,
+        }
+    }
+
+    /// Map without interpolation, see `Colormap::nth` for picking series colors.
+    pub fn categorical(colors: Vec<RGBA<f32>>) -> Self {
+        Self {
+            categorical: true,
+            ..Self::new(colors)
+        }
+    }
+
+    /// Perceptually uniform, blue to yellow, default of matplotlib.
+    pub fn viridis() -> Self {
+        Self::from_polynomial(&[
+            [0.2777273272234177, 0.005407344544966578, 0.3340998053353061],
+            [0.1050930431085774, 1.404613529898575, 1.384590162594685],
+            [-0.3308618287255563, 0.214847559468213, 0.09509516302823659],
+            [-4.634230498983486, -5.799100973351585, -19.33244095627987],
+            [6.228269936347081, 14.17993336680509, 56.69055260068105],
+            [4.776384997670288, -13.74514537774601, -65.35303263337234],
+            [-5.435455855934631, 4.645852612178535, 26.3124352495832],
+        ])
+    }
+
+    /// Perceptually uniform, black through purple to light yellow.
+    pub fn magma() -> Self {
+        Self::from_polynomial(&[
+            [
+                -0.002136485053939582,
+                -0.000749655052795221,
+                -0.005386127855323933,
+            ],
+            [0.2516605407371642, 0.6775232436837668, 2.494026599312351],
+            [8.353717279216625, -3.577719514958484, 0.3144679030132573],
+            [-27.66873308576866, 14.26473078096533, -13.64921318813922],
+            [52.17613981234068, -27.94360607168351, 12.94416944238394],
+            [-50.76852536473588, 29.04658282127291, 4.23415299384598],
+            [18.65570506591883, -11.48977351997711, -5.601961508734096],
+        ])
+    }
+
+    /// Perceptually uniform, black through red to yellow.
+    pub fn inferno() -> Self {
+        Self::from_polynomial(&[
+            [
+                0.0002189403691192265,
+                0.001651004631001012,
+                -0.01948089843709184,
+            ],
+            [0.1065134194856116, 0.5639564367884091, 3.932712388889277],
+            [11.60249308247187, -3.972853965665698, -15.9423941062914],
+            [-41.70399613139459, 17.43639888205313, 44.35414519872813],
+            [77.162935699427, -33.40235894210092, -81.80730925738993],
+            [-71.31942824499214, 32.62606426397723, 73.20951985803202],
+            [25.13112622477341, -12.24266895238567, -23.07032500287172],
+        ])
+    }
+
+    /// Perceptually uniform, blue through magenta to yellow.
+    pub fn plasma() -> Self {
+        Self::from_polynomial(&[
+            [0.05873234392399702, 0.02333670892565664, 0.5433401826748754],
+            [2.176514634195958, 0.2383834171260182, 0.7539604599784036],
+            [-2.689460476458034, -7.455851135738909, 3.110799939717086],
+            [6.130348345893603, 42.3461881477227, -28.51885465332158],
+            [-11.10743619062271, -82.66631109428045, 60.13984767418263],
+            [10.02306557647065, 71.41361770095349, -54.07218655560067],
+            [-3.658713842777788, -22.93153465461149, 18.19190778539828],
+        ])
+    }
+
+    /// Perceptually uniform and readable with color vision deficiency, blue to yellow.
+    pub fn cividis() -> Self {
+        Self::from_hex(&[
+            0x00224e, 0x123570, 0x3b496c, 0x575d6d, 0x707173, 0x8a8779, 0xa69d75, 0xc4b56c,
+            0xe4cf5b, 0xfee838,
+        ])
+    }
+
+    /// Continuous map from `low` through `mid` to `high`, eg. for values around 0.
+    pub fn diverging(low: RGBA<f32>, mid: RGBA<f32>, high: RGBA<f32>) -> Self {
+        Self::new(vec![low, mid, high])
+    }
+
+    /// Diverging blue to red through light gray, by Kenneth Moreland.
+    pub fn coolwarm() -> Self {
+        Self::diverging(
+            RGBA::new(0.230, 0.299, 0.754, 1.),
+            RGBA::new(0.865, 0.865, 0.865, 1.),
+            RGBA::new(0.706, 0.016, 0.150, 1.),
+        )
+    }
+
+    /// Diverging red to blue through white, from ColorBrewer.
+    pub fn red_blue() -> Self {
+        Self::from_hex(&[
+            0x67001f, 0xb2182b, 0xd6604d, 0xf4a582, 0xfddbc7, 0xf7f7f7, 0xd1e5f0, 0x92c5de,
+            0x4393c3, 0x2166ac, 0x053061,
+        ])
+    }
+
+    /// 10 categorical colors of d3 and matplotlib.
+    pub fn category10() -> Self {
+        Self::categorical(hex_colors(&[
+            0x1f77b4, 0xff7f0e, 0x2ca02c, 0xd62728, 0x9467bd, 0x8c564b, 0xe377c2, 0x7f7f7f,
+            0xbcbd22, 0x17becf,
+        ]))
+    }
+
+    /// 8 categorical colors that are distinguishable with color vision deficiency.
+    pub fn okabe_ito() -> Self {
+        Self::categorical(hex_colors(&[
+            0xe69f00, 0x56b4e9, 0x009e73, 0xf0e442, 0x0072b2, 0xd55e00, 0xcc79a7, 0x000000,
+        ]))
+    }
+
+    pub fn reversed(&self) -> Self {
+        let mut reversed = self.clone();
+        reversed.colors.reverse();
+        reversed
+    }
+
+    pub fn colors(&self) -> &[RGBA<f32>] {
+        &self.colors
+    }
+
+    pub fn is_categorical(&self) -> bool {
+        self.categorical
+    }
+
+    /// Color at `t` which is clamped to `0..=1`, NaN maps to the start.
+    pub fn sample(&self, t: f32) -> RGBA<f32> {
+        let t = if t.is_nan() { 0. } else { t.max(0.).min(1.) };
+        let last = self.colors.len() - 1;
+        if self.categorical {
+            let i = (t * self.colors.len() as f32) as usize;
+            return self.colors[i.min(last)];
+        }
+        let x = t * last as f32;
+        let i = (x as usize).min(last);
+        let next = (i + 1).min(last);
+        lerp(self.colors[i], self.colors[next], x - i as f32)
+    }
+
+    /// Color of the `i`-th series, categorical colors repeat, continuous maps are split evenly
+    /// between `count` series.
+    pub fn nth(&self, i: usize, count: usize) -> RGBA<f32> {
+        if self.categorical {
+            self.colors[i % self.colors.len()]
+        } else if count <= 1 {
+            self.sample(0.)
+        } else {
+            self.sample(i as f32 / (count - 1) as f32)
+        }
+    }
+
+    /// Samples `width` colors evenly from start to end, `width` must be at least 1.
+    pub fn bake(&self, width: u32) -> Vec<RGBA<f32>> {
+        assert!(width > 0, "colormap needs at least one texel");
+        if width == 1 {
+            return vec![self.sample(0.)];
+        }
+        (0..width)
+            .map(|i| self.sample(i as f32 / (width - 1) as f32))
+            .collect()
+    }
+
+    /// Bakes `width` colors into an `Rgba32Float` 1D texture, eg. for `ColormapBindGroup`,
+    /// `width` must be at least 1.
+    /// Texels hold the same values as `Colormap::sample`.
+    /// Categorical maps should use width that is a multiple of the number of colors.
+    pub fn create_texture(&self, device: &Device, queue: &Queue, width: u32) -> Texture {
+        assert!(width > 0, "colormap texture needs at least one texel");
+        let size = wgpu::Extent3d {
+            width,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("niobe-core::Colormap"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D1,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        });
+        let texels = self.bake(width);
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                aspect: wgpu::TextureAspect::All,
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+            },
+            bytemuck::cast_slice(&texels),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(width * std::mem::size_of::<RGBA<f32>>() as u32),
+                rows_per_image: None,
+            },
+            size,
+        );
+        texture
+    }
+
+    /// Evaluates a polynomial fit with coefficients from the constant term up.
+    /// See https://www.shadertoy.com/view/WlfXRN
+    fn from_polynomial(coefficients: &[[f32; 3]]) -> Self {
+        let colors = (0..TABLE_SIZE)
+            .map(|i| {
+                let t = i as f32 / (TABLE_SIZE - 1) as f32;
+                let mut rgb = [0f32; 3];
+                for c in coefficients.iter().rev() {
+                    for (channel, c) in rgb.iter_mut().zip(c) {
+                        *channel = *channel * t + c;
+                    }
+                }
+                let [r, g, b] = rgb;
+                RGBA::new(r.max(0.).min(1.), g.max(0.).min(1.), b.max(0.).min(1.), 1.)
+            })
+            .collect();
+        Self::new(colors)
+    }
+
+    fn from_hex(colors: &[u32]) -> Self {
+        Self::new(hex_colors(colors))
+    }
+}
+
+impl Default for Colormap {
+    fn default() -> Self {
+        Self::viridis()
+    }
+}
+
+fn hex_colors(colors: &[u32]) -> Vec<RGBA<f32>> {
+    colors
+        .iter()
+        .map(|x| {
+            RGBA::new(
+                ((x >> 16) & 0xff) as f32 / 255.,
+                ((x >> 8) & 0xff) as f32 / 255.,
+                (x & 0xff) as f32 / 255.,
+                1.,
+            )
+        })
+        .collect()
+}
+
+fn lerp(a: RGBA<f32>, b: RGBA<f32>, t: f32) -> RGBA<f32> {
+    RGBA::new(
+        a.r + (b.r - a.r) * t,
+        a.g + (b.g - a.g) * t,
+        a.b + (b.b - a.b) * t,
+        a.a + (b.a - a.a) * t,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gray(x: f32) -> RGBA<f32> {
+        RGBA::new(x, x, x, 1.)
+    }
+
+    fn black_white() -> Colormap {
+        Colormap::new(vec![gray(0.), gray(1.)])
+    }
+
+    fn three() -> Colormap {
+        Colormap::categorical(vec![gray(0.), gray(0.5), gray(1.)])
+    }
+
+    #[test]
+    fn sample_interpolates() {
+        let map = black_white();
+        assert_eq!(map.sample(0.), gray(0.));
+        assert_eq!(map.sample(0.25), gray(0.25));
+        assert_eq!(map.sample(1.), gray(1.));
+    }
+
+    #[test]
+    fn sample_clamps() {
+        let map = black_white();
+        assert_eq!(map.sample(-1.), gray(0.));
+        assert_eq!(map.sample(2.), gray(1.));
+        assert_eq!(map.sample(f32::NAN), gray(0.));
+    }
+
+    #[test]
+    fn sample_categorical_bins() {
+        let map = three();
+        assert_eq!(map.sample(0.), gray(0.));
+        assert_eq!(map.sample(0.3), gray(0.));
+        assert_eq!(map.sample(0.4), gray(0.5));
+        assert_eq!(map.sample(0.7), gray(1.));
+        assert_eq!(map.sample(1.), gray(1.));
+    }
+
+    #[test]
+    fn sample_single_color() {
+        let map = Colormap::new(vec![gray(0.5)]);
+        assert_eq!(map.sample(0.), gray(0.5));
+        assert_eq!(map.sample(1.), gray(0.5));
+    }
+
+    #[test]
+    fn nth_categorical_repeats() {
+        let map = three();
+        assert_eq!(map.nth(1, 10), gray(0.5));
+        assert_eq!(map.nth(3, 10), gray(0.));
+        assert_eq!(map.nth(5, 10), gray(1.));
+    }
+
+    #[test]
+    fn nth_continuous_splits_evenly() {
+        let map = black_white();
+        assert_eq!(map.nth(0, 5), gray(0.));
+        assert_eq!(map.nth(2, 5), gray(0.5));
+        assert_eq!(map.nth(4, 5), gray(1.));
+        assert_eq!(map.nth(0, 1), gray(0.));
+    }
+
+    #[test]
+    fn bake_spans_whole_map() {
+        let baked = black_white().bake(5);
+        let expected: Vec<_> = [0., 0.25, 0.5, 0.75, 1.].iter().map(|&x| gray(x)).collect();
+        assert_eq!(baked, expected);
+        assert_eq!(black_white().bake(1), vec![gray(0.)]);
+    }
+
+    #[test]
+    fn bake_categorical_multiple_of_colors() {
+        let baked = three().bake(6);
+        let expected: Vec<_> = [0., 0., 0.5, 0.5, 1., 1.]
+            .iter()
+            .map(|&x| gray(x))
+            .collect();
+        assert_eq!(baked, expected);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bake_empty() {
+        black_white().bake(0);
+    }
+}
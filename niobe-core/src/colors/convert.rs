@@ -0,0 +1,214 @@
+use epaint::Color32;
+use rgb::RGBA;
+
+/// Conversions and adjustments of `RGBA<f32>` colors, channels are sRGB in `0..=1` with straight
+/// (not premultiplied) alpha, like the constants in `colors`.
+pub trait ColorExt: Sized {
+    fn to_color32(self) -> Color32;
+    fn to_wgpu(self) -> wgpu::Color;
+    /// Converts sRGB channels into linear ones, alpha is unchanged.
+    fn to_linear(self) -> Self;
+    /// Converts linear channels into sRGB ones, alpha is unchanged.
+    fn to_srgb(self) -> Self;
+    /// Increases HSL lightness by `amount` in `0..=1`.
+    fn lighten(self, amount: f32) -> Self;
+    /// Decreases HSL lightness by `amount` in `0..=1`.
+    fn darken(self, amount: f32) -> Self;
+    fn with_alpha(self, alpha: f32) -> Self;
+}
+
+impl ColorExt for RGBA<f32> {
+    fn to_color32(self) -> Color32 {
+        Color32::from_rgba_unmultiplied(to_u8(self.r), to_u8(self.g), to_u8(self.b), to_u8(self.a))
+    }
+
+    fn to_wgpu(self) -> wgpu::Color {
+        wgpu::Color {
+            r: self.r as f64,
+            g: self.g as f64,
+            b: self.b as f64,
+            a: self.a as f64,
+        }
+    }
+
+    fn to_linear(self) -> Self {
+        RGBA::new(
+            srgb_to_linear(self.r),
+            srgb_to_linear(self.g),
+            srgb_to_linear(self.b),
+            self.a,
+        )
+    }
+
+    fn to_srgb(self) -> Self {
+        RGBA::new(
+            linear_to_srgb(self.r),
+            linear_to_srgb(self.g),
+            linear_to_srgb(self.b),
+            self.a,
+        )
+    }
+
+    fn lighten(self, amount: f32) -> Self {
+        let [h, s, l] = rgb_to_hsl(self.r, self.g, self.b);
+        hsl_to_rgba(h, s, (l + amount).min(1.), self.a)
+    }
+
+    fn darken(self, amount: f32) -> Self {
+        let [h, s, l] = rgb_to_hsl(self.r, self.g, self.b);
+        hsl_to_rgba(h, s, (l - amount).max(0.), self.a)
+    }
+
+    fn with_alpha(self, alpha: f32) -> Self {
+        RGBA { a: alpha, ..self }
+    }
+}
+
+/// `Color32` stores premultiplied alpha, this returns straight alpha.
+pub fn from_color32(color: Color32) -> RGBA<f32> {
+    let a = color.a();
+    if a == 0 {
+        return RGBA::new(0., 0., 0., 0.);
+    }
+    let unmultiply = |x: u8| (x as f32 / a as f32).min(1.);
+    RGBA::new(
+        unmultiply(color.r()),
+        unmultiply(color.g()),
+        unmultiply(color.b()),
+        a as f32 / 255.,
+    )
+}
+
+/// Keeps channels as they are stored, premultiplied.
+pub fn color32_to_wgpu(color: Color32) -> wgpu::Color {
+    wgpu::Color {
+        r: color.r() as f64 / 255.,
+        g: color.g() as f64 / 255.,
+        b: color.b() as f64 / 255.,
+        a: color.a() as f64 / 255.,
+    }
+}
+
+pub fn srgb_to_linear(x: f32) -> f32 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+pub fn linear_to_srgb(x: f32) -> f32 {
+    if x <= 0.0031308 {
+        x * 12.92
+    } else {
+        1.055 * x.powf(1. / 2.4) - 0.055
+    }
+}
+
+/// Hue is in degrees, saturation and lightness in `0..=1`.
+pub fn hsl_to_rgba(hue: f32, saturation: f32, lightness: f32, alpha: f32) -> RGBA<f32> {
+    let h = hue.rem_euclid(360.) / 60.;
+    let c = (1. - (2. * lightness - 1.).abs()) * saturation;
+    let x = c * (1. - (h % 2. - 1.).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.),
+        1 => (x, c, 0.),
+        2 => (0., c, x),
+        3 => (0., x, c),
+        4 => (x, 0., c),
+        _ => (c, 0., x),
+    };
+    let m = lightness - c / 2.;
+    RGBA::new(r + m, g + m, b + m, alpha)
+}
+
+/// Returns hue in degrees, saturation and lightness.
+pub fn rgb_to_hsl(r: f32, g: f32, b: f32) -> [f32; 3] {
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let l = (max + min) / 2.;
+    let d = max - min;
+    if d == 0. {
+        return [0., 0., l];
+    }
+    let s = d / (1. - (2. * l - 1.).abs());
+    let h = if max == r {
+        ((g - b) / d).rem_euclid(6.)
+    } else if max == g {
+        (b - r) / d + 2.
+    } else {
+        (r - g) / d + 4.
+    };
+    [h * 60., s, l]
+}
+
+fn to_u8(x: f32) -> u8 {
+    (x.max(0.).min(1.) * 255.).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn rgba(hue: f32, saturation: f32, lightness: f32) -> [f32; 4] {
+        let x = hsl_to_rgba(hue, saturation, lightness, 0.5);
+        [x.r, x.g, x.b, x.a]
+    }
+
+    #[test]
+    fn hsl_to_rgba_hues() {
+        assert_close(&rgba(0., 1., 0.5), &[1., 0., 0., 0.5]);
+        assert_close(&rgba(60., 1., 0.5), &[1., 1., 0., 0.5]);
+        assert_close(&rgba(120., 1., 0.5), &[0., 1., 0., 0.5]);
+        assert_close(&rgba(180., 1., 0.5), &[0., 1., 1., 0.5]);
+        assert_close(&rgba(240., 1., 0.5), &[0., 0., 1., 0.5]);
+        assert_close(&rgba(300., 1., 0.5), &[1., 0., 1., 0.5]);
+    }
+
+    #[test]
+    fn hsl_to_rgba_wraps_hue() {
+        assert_close(&rgba(360., 1., 0.5), &rgba(0., 1., 0.5));
+        assert_close(&rgba(-120., 1., 0.5), &rgba(240., 1., 0.5));
+    }
+
+    #[test]
+    fn hsl_to_rgba_lightness() {
+        assert_close(&rgba(0., 1., 0.25), &[0.5, 0., 0., 0.5]);
+        assert_close(&rgba(0., 1., 0.75), &[1., 0.5, 0.5, 0.5]);
+        assert_close(&rgba(200., 0., 0.3), &[0.3, 0.3, 0.3, 0.5]);
+    }
+
+    #[test]
+    fn rgb_to_hsl_roundtrip() {
+        assert_close(&rgb_to_hsl(1., 0., 0.), &[0., 1., 0.5]);
+        assert_close(&rgb_to_hsl(0., 0., 1.), &[240., 1., 0.5]);
+        assert_close(&rgb_to_hsl(0.4, 0.4, 0.4), &[0., 0., 0.4]);
+        for &(h, s, l) in &[(30., 0.5, 0.4), (150., 0.8, 0.7), (330., 0.3, 0.2)] {
+            let x = hsl_to_rgba(h, s, l, 1.);
+            assert_close(&rgb_to_hsl(x.r, x.g, x.b), &[h, s, l]);
+        }
+    }
+
+    #[test]
+    fn lighten_darken() {
+        let red = RGBA::new(1., 0., 0., 0.5);
+        let lighter = red.lighten(0.25);
+        assert_close(
+            &[lighter.r, lighter.g, lighter.b, lighter.a],
+            &[1., 0.5, 0.5, 0.5],
+        );
+        let darker = red.darken(0.25);
+        assert_close(
+            &[darker.r, darker.g, darker.b, darker.a],
+            &[0.5, 0., 0., 0.5],
+        );
+        let white = red.lighten(1.);
+        assert_close(&[white.r, white.g, white.b], &[1., 1., 1.]);
+    }
+}
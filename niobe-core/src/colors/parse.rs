@@ -0,0 +1,365 @@
+use super::convert::{hsl_to_rgba, ColorExt};
+use super::*;
+use epaint::Color32;
+use rgb::RGBA;
+use std::str::FromStr;
+
+#[derive(Debug, Display, Clone, PartialEq)]
+pub enum ParseColorError {
+    #[display(fmt = "invalid hex color `{}`", _0)]
+    Hex(String),
+    #[display(fmt = "invalid color function `{}`", _0)]
+    Function(String),
+    #[display(fmt = "unknown color name `{}`", _0)]
+    Name(String),
+}
+
+impl std::error::Error for ParseColorError {}
+
+/// Parses `#rgb`, `#rgba`, `#rrggbb`, `#rrggbbaa`, `rgb()`, `rgba()`, `hsl()`, `hsla()` and CSS
+/// color names, case insensitive.
+/// Function arguments are separated by commas or spaces with an optional `/` before alpha,
+/// rgb channels are in `0..=255` or percentages and alpha in `0..=1` or a percentage.
+pub fn parse_color(s: &str) -> Result<RGBA<f32>, ParseColorError> {
+    let s = s.trim().to_ascii_lowercase();
+    if let Some(hex) = s.strip_prefix('#') {
+        return parse_hex(hex).ok_or_else(|| ParseColorError::Hex(s.clone()));
+    }
+    if let Some(open) = s.find('(') {
+        return parse_function(&s[..open], &s[open + 1..])
+            .ok_or_else(|| ParseColorError::Function(s.clone()));
+    }
+    color_by_name(&s).ok_or(ParseColorError::Name(s))
+}
+
+/// Same as `parse_color` but converted into `Color32`.
+pub fn parse_color32(s: &str) -> Result<Color32, ParseColorError> {
+    parse_color(s).map(ColorExt::to_color32)
+}
+
+/// CSS color name, eg. `darkslategray` or `dark_slate_grey`.
+pub fn color_by_name(name: &str) -> Option<RGBA<f32>> {
+    let name = name
+        .to_ascii_lowercase()
+        .replace('_', "")
+        .replace("grey", "gray");
+    if name == "transparent" {
+        return Some(RGBA::new(0., 0., 0., 0.));
+    }
+    NAMES
+        .iter()
+        .find(|(x, _)| *x == name)
+        .map(|(_, color)| *color)
+}
+
+fn parse_hex(hex: &str) -> Option<RGBA<f32>> {
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digit = |i: usize| u8::from_str_radix(&hex[i..i + 1], 16).ok();
+    let byte = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    let channels: Vec<u8> = match hex.len() {
+        // short form repeats every digit
+        3 | 4 => (0..hex.len())
+            .map(|i| digit(i).map(|x| x * 17))
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len() / 2)
+            .map(|i| byte(i * 2))
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    let channel = |i: usize| channels.get(i).map_or(1., |x| *x as f32 / 255.);
+    Some(RGBA::new(channel(0), channel(1), channel(2), channel(3)))
+}
+
+fn parse_function(name: &str, args: &str) -> Option<RGBA<f32>> {
+    let args = args.trim_end().strip_suffix(')')?;
+    let args: Vec<&str> = args
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .collect();
+    let alpha = match args.len() {
+        3 => 1.,
+        4 => parse_unit(args[3], 1.)?,
+        _ => return None,
+    };
+    match name.trim() {
+        "rgb" | "rgba" => Some(RGBA::new(
+            parse_unit(args[0], 255.)?,
+            parse_unit(args[1], 255.)?,
+            parse_unit(args[2], 255.)?,
+            alpha,
+        )),
+        "hsl" | "hsla" => {
+            let hue = args[0].strip_suffix("deg").unwrap_or(args[0]);
+            Some(hsl_to_rgba(
+                f32::from_str(hue).ok()?,
+                parse_unit(args[1], 100.)?,
+                parse_unit(args[2], 100.)?,
+                alpha,
+            ))
+        }
+        _ => None,
+    }
+}
+
+/// Number divided by `max` or a percentage, clamped to `0..=1`.
+fn parse_unit(s: &str, max: f32) -> Option<f32> {
+    let x = match s.strip_suffix('%') {
+        Some(percent) => f32::from_str(percent).ok()? / 100.,
+        None => f32::from_str(s).ok()? / max,
+    };
+    Some(x.max(0.).min(1.))
+}
+
+const NAMES: &[(&str, RGBA<f32>)] = &[
+    ("white", WHITE),
+    ("silver", SILVER),
+    ("gray", GRAY),
+    ("black", BLACK),
+    ("red", RED),
+    ("maroon", MAROON),
+    ("yellow", YELLOW),
+    ("olive", OLIVE),
+    ("lime", LIME),
+    ("green", GREEN),
+    ("aqua", AQUA),
+    ("teal", TEAL),
+    ("blue", BLUE),
+    ("navy", NAVY),
+    ("fuchsia", FUCHSIA),
+    ("purple", PURPLE),
+    ("pink", PINK),
+    ("lightpink", LIGHT_PINK),
+    ("hotpink", HOT_PINK),
+    ("deeppink", DEEP_PINK),
+    ("palevioletred", PALE_VIOLET_RED),
+    ("mediumvioletred", MEDIUM_VIOLET_RED),
+    ("lightsalmon", LIGHT_SALMON),
+    ("salmon", SALMON),
+    ("darksalmon", DARK_SALMON),
+    ("lightcoral", LIGHT_CORAL),
+    ("indianred", INDIAN_RED),
+    ("crimson", CRIMSON),
+    ("firebrick", FIREBRICK),
+    ("darkred", DARK_RED),
+    ("orangered", ORANGE_RED),
+    ("tomato", TOMATO),
+    ("coral", CORAL),
+    ("darkorange", DARK_ORANGE),
+    ("orange", ORANGE),
+    ("lightyellow", LIGHT_YELLOW),
+    ("lemonchiffon", LEMON_CHIFFON),
+    ("lightgoldenrodyellow", LIGHT_GOLDENROD_YELLOW),
+    ("papayawhip", PAPAYA_WHIP),
+    ("moccasin", MOCCASIN),
+    ("peachpuff", PEACH_PUFF),
+    ("palegoldenrod", PALE_GOLDENROD),
+    ("khaki", KHAKI),
+    ("darkkhaki", DARK_KHAKI),
+    ("gold", GOLD),
+    ("cornsilk", CORNSILK),
+    ("blanchedalmond", BLANCHED_ALMOND),
+    ("bisque", BISQUE),
+    ("navajowhite", NAVAJO_WHITE),
+    ("wheat", WHEAT),
+    ("burlywood", BURLYWOOD),
+    ("tan", TAN),
+    ("rosybrown", ROSY_BROWN),
+    ("sandybrown", SANDY_BROWN),
+    ("goldenrod", GOLDENROD),
+    ("darkgoldenrod", DARK_GOLDENROD),
+    ("peru", PERU),
+    ("chocolate", CHOCOLATE),
+    ("saddlebrown", SADDLE_BROWN),
+    ("sienna", SIENNA),
+    ("brown", BROWN),
+    ("darkolivegreen", DARK_OLIVE_GREEN),
+    ("olivedrab", OLIVE_DRAB),
+    ("yellowgreen", YELLOW_GREEN),
+    ("limegreen", LIME_GREEN),
+    ("lawngreen", LAWN_GREEN),
+    ("chartreuse", CHARTREUSE),
+    ("greenyellow", GREEN_YELLOW),
+    ("springgreen", SPRING_GREEN),
+    ("mediumspringgreen", MEDIUM_SPRING_GREEN),
+    ("lightgreen", LIGHT_GREEN),
+    ("palegreen", PALE_GREEN),
+    ("darkseagreen", DARK_SEA_GREEN),
+    ("mediumaquamarine", MEDIUM_AQUAMARINE),
+    ("mediumseagreen", MEDIUM_SEA_GREEN),
+    ("seagreen", SEA_GREEN),
+    ("forestgreen", FOREST_GREEN),
+    ("darkgreen", DARK_GREEN),
+    ("cyan", CYAN),
+    ("lightcyan", LIGHT_CYAN),
+    ("paleturquoise", PALE_TURQUOISE),
+    ("aquamarine", AQUAMARINE),
+    ("turquoise", TURQUOISE),
+    ("mediumturquoise", MEDIUM_TURQUOISE),
+    ("darkturquoise", DARK_TURQUOISE),
+    ("lightseagreen", LIGHT_SEA_GREEN),
+    ("cadetblue", CADET_BLUE),
+    ("darkcyan", DARK_CYAN),
+    ("lightsteelblue", LIGHT_STEEL_BLUE),
+    ("powderblue", POWDER_BLUE),
+    ("lightblue", LIGHT_BLUE),
+    ("skyblue", SKY_BLUE),
+    ("lightskyblue", LIGHT_SKY_BLUE),
+    ("deepskyblue", DEEP_SKY_BLUE),
+    ("dodgerblue", DODGER_BLUE),
+    ("cornflowerblue", CORNFLOWER_BLUE),
+    ("steelblue", STEEL_BLUE),
+    ("royalblue", ROYAL_BLUE),
+    ("mediumblue", MEDIUM_BLUE),
+    ("darkblue", DARK_BLUE),
+    ("midnightblue", MIDNIGHT_BLUE),
+    ("lavender", LAVENDER),
+    ("thistle", THISTLE),
+    ("plum", PLUM),
+    ("violet", VIOLET),
+    ("orchid", ORCHID),
+    ("magenta", MAGENTA),
+    ("mediumorchid", MEDIUM_ORCHID),
+    ("mediumpurple", MEDIUM_PURPLE),
+    ("blueviolet", BLUE_VIOLET),
+    ("darkviolet", DARK_VIOLET),
+    ("darkorchid", DARK_ORCHID),
+    ("darkmagenta", DARK_MAGENTA),
+    ("indigo", INDIGO),
+    ("darkslateblue", DARK_SLATE_BLUE),
+    ("slateblue", SLATE_BLUE),
+    ("mediumslateblue", MEDIUM_SLATE_BLUE),
+    ("snow", SNOW),
+    ("honeydew", HONEYDEW),
+    ("mintcream", MINT_CREAM),
+    ("azure", AZURE),
+    ("aliceblue", ALICE_BLUE),
+    ("ghostwhite", GHOST_WHITE),
+    ("whitesmoke", WHITE_SMOKE),
+    ("seashell", SEASHELL),
+    ("beige", BEIGE),
+    ("oldlace", OLD_LACE),
+    ("floralwhite", FLORAL_WHITE),
+    ("ivory", IVORY),
+    ("antiquewhite", ANTINQUE_WHITE),
+    ("linen", LINEN),
+    ("lavenderblush", LAVENDER_BLUSH),
+    ("mistyrose", MISTY_ROSE),
+    ("gainsboro", GAINSBORO),
+    ("lightgray", LIGHT_GRAY),
+    ("darkgray", DARK_GRAY),
+    ("dimgray", DIM_GRAY),
+    ("lightslategray", LIGHT_SLATE_GRAY),
+    ("slategray", SLATE_GRAY),
+    ("darkslategray", DARK_SLATE_GRAY),
+    (
+        "rebeccapurple",
+        RGBA {
+            r: 0x66 as f32 / 255.,
+            g: 0x33 as f32 / 255.,
+            b: 0x99 as f32 / 255.,
+            a: 1.,
+        },
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: RGBA<f32>, expected: RGBA<f32>) {
+        let channels = |x: RGBA<f32>| [x.r, x.g, x.b, x.a];
+        for (a, e) in channels(actual).iter().zip(&channels(expected)) {
+            assert!((a - e).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+        }
+    }
+
+    fn parse(s: &str) -> RGBA<f32> {
+        parse_color(s).unwrap()
+    }
+
+    #[test]
+    fn hex_lengths() {
+        assert_close(parse("#f00"), RGBA::new(1., 0., 0., 1.));
+        assert_close(parse("#f008"), RGBA::new(1., 0., 0., 0x88 as f32 / 255.));
+        assert_close(parse("#ff8000"), RGBA::new(1., 0x80 as f32 / 255., 0., 1.));
+        assert_close(
+            parse("#ff000080"),
+            RGBA::new(1., 0., 0., 0x80 as f32 / 255.),
+        );
+        assert_close(parse(" #FF0000 "), RGBA::new(1., 0., 0., 1.));
+    }
+
+    #[test]
+    fn invalid_hex() {
+        for s in &["#", "#ff", "#ff000", "#ff0000000", "#ggg"] {
+            assert!(
+                matches!(parse_color(s), Err(ParseColorError::Hex(_))),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn rgb_function() {
+        assert_close(parse("rgb(255, 0, 0)"), RGBA::new(1., 0., 0., 1.));
+        assert_close(parse("rgba(255, 0, 0, 0.25)"), RGBA::new(1., 0., 0., 0.25));
+        assert_close(parse("rgb(100%, 50%, 0%)"), RGBA::new(1., 0.5, 0., 1.));
+        assert_close(parse("rgb(300, -5, 0)"), RGBA::new(1., 0., 0., 1.));
+    }
+
+    #[test]
+    fn rgb_slash_alpha() {
+        assert_close(parse("rgb(255 0 0 / 50%)"), RGBA::new(1., 0., 0., 0.5));
+        assert_close(parse("rgb(255 0 0 / 0.5)"), RGBA::new(1., 0., 0., 0.5));
+    }
+
+    #[test]
+    fn hsl_percentages() {
+        assert_close(parse("hsl(120, 100%, 50%)"), RGBA::new(0., 1., 0., 1.));
+        assert_close(parse("hsl(0deg 100% 25%)"), RGBA::new(0.5, 0., 0., 1.));
+        assert_close(
+            parse("hsla(240, 100%, 50%, 0.5)"),
+            RGBA::new(0., 0., 1., 0.5),
+        );
+        assert_close(parse("hsl(0 0% 100% / 25%)"), RGBA::new(1., 1., 1., 0.25));
+    }
+
+    #[test]
+    fn invalid_function() {
+        for s in &[
+            "rgb(1, 2)",
+            "rgb(1, 2, 3, 4, 5)",
+            "rgb(a, 0, 0)",
+            "rgb(0, 0, 0",
+            "foo(0, 0, 0)",
+        ] {
+            assert!(
+                matches!(parse_color(s), Err(ParseColorError::Function(_))),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(parse("red"), RED);
+        assert_eq!(parse("RebeccaPurple"), parse("#663399"));
+        assert_eq!(parse("transparent"), RGBA::new(0., 0., 0., 0.));
+        assert_eq!(
+            parse_color("reddish"),
+            Err(ParseColorError::Name("reddish".to_string()))
+        );
+    }
+
+    #[test]
+    fn grey_aliases() {
+        assert_eq!(parse("grey"), GRAY);
+        assert_eq!(parse("LightGrey"), LIGHT_GRAY);
+        assert_eq!(color_by_name("dark_slate_grey"), Some(DARK_SLATE_GRAY));
+        assert_eq!(color_by_name("dim_gray"), Some(DIM_GRAY));
+    }
+}
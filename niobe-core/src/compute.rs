@@ -0,0 +1,3 @@
+pub mod minmax;
+
+pub use minmax::MinMax;
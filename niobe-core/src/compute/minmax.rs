@@ -0,0 +1,254 @@
+use crate::buffer::BufferSlice;
+use bytemuck::{Pod, Zeroable};
+use std::iter;
+use std::mem;
+use std::num::NonZeroU64;
+use wgpu::{
+    BindGroup, BindGroupLayout, BindingResource, BufferAddress, BufferAsyncError, BufferBinding,
+    BufferUsages, ComputePipeline, Device, DynamicOffset, Maintain, MapMode, Queue,
+};
+
+/// Must match `workgroup_size`, shared arrays and the first reduction step (half of it) in
+/// minmax.wgsl.
+const WORKGROUP_SIZE: u32 = 64;
+/// Threads loop over the grid when there are more elements, this caps the size of the first
+/// stage output.
+const MAX_WORKGROUPS: u32 = 1024;
+const MAX_STAGES: usize = 8;
+const PAIR_SIZE: BufferAddress = 2 * mem::size_of::<f32>() as BufferAddress;
+
+/// Padded to 256 bytes so that every stage has its own dynamic offset, padding is explicit so that
+/// `Pod` is sound.
+#[repr(C, align(256))]
+#[derive(Copy, Clone, Debug)]
+struct Args {
+    offset: u32,
+    stride: u32,
+    len: u32,
+    _pad: [u32; 61],
+}
+
+impl Args {
+    fn new(offset: u32, stride: u32, len: u32) -> Self {
+        Self {
+            offset,
+            stride,
+            len,
+            _pad: [0; 61],
+        }
+    }
+}
+
+unsafe impl Pod for Args {}
+unsafe impl Zeroable for Args {}
+
+/// Finds min and max of f32 values in a gpu buffer without copying the data back to the cpu.
+/// Values are read with an offset and a stride so that interleaved data can be used,
+/// eg. y values of a `Point2d` buffer have offset 1 and stride 2. NaN values are ignored.
+///
+/// Buffers that are passed in must be created with `BufferUsages::STORAGE`.
+pub struct MinMax {
+    strided_pipeline: ComputePipeline,
+    pairs_pipeline: ComputePipeline,
+    layout: BindGroupLayout,
+    args: wgpu::Buffer,
+    scratch: [wgpu::Buffer; 2],
+    readback: wgpu::Buffer,
+}
+
+impl MinMax {
+    pub fn new(device: &Device) -> Self {
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("minmax shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("minmax.wgsl").into()),
+        });
+        let storage_entry = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                storage_entry(1, true),
+                storage_entry(2, false),
+            ],
+            label: Some("minmax bind group layout"),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("minmax pipeline layout"),
+            bind_group_layouts: &[&layout],
+            push_constant_ranges: &[],
+        });
+        let create_pipeline = |entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("minmax pipeline"),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point,
+            })
+        };
+        let create_scratch = || {
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("minmax scratch"),
+                size: MAX_WORKGROUPS as BufferAddress * PAIR_SIZE,
+                usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            })
+        };
+        Self {
+            strided_pipeline: create_pipeline("minmax_strided"),
+            pairs_pipeline: create_pipeline("minmax_pairs"),
+            layout,
+            args: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("minmax args"),
+                size: (MAX_STAGES * mem::size_of::<Args>()) as BufferAddress,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            }),
+            scratch: [create_scratch(), create_scratch()],
+            readback: device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("minmax readback"),
+                size: PAIR_SIZE,
+                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+        }
+    }
+
+    /// Returns (min, max) of every `stride`-th f32 in `slice` starting at `offset`,
+    /// both are in f32 units relative to the start of the slice.
+    /// If there are no values (f32::MAX, f32::MIN) is returned.
+    pub async fn compute<T: Pod>(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        slice: &BufferSlice<'_, T>,
+        offset: u32,
+        stride: u32,
+    ) -> Result<(f32, f32), BufferAsyncError> {
+        debug_assert!(stride > 0);
+        debug_assert_eq!(mem::size_of::<T>() % mem::size_of::<f32>(), 0);
+        let float_size = mem::size_of::<f32>() as BufferAddress;
+        let range = slice.raw_addres_range();
+        // storage bindings need aligned offsets so the whole buffer is bound instead
+        let start = (range.start / float_size) as u32 + offset;
+        let n_floats = ((range.end - range.start) / float_size) as u32;
+        let len = if offset < n_floats {
+            (n_floats - offset + stride - 1) / stride
+        } else {
+            0
+        };
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("niobe-core::MinMax"),
+        });
+        let mut bind_groups = Vec::with_capacity(MAX_STAGES);
+        let mut dispatches = Vec::with_capacity(MAX_STAGES);
+        let mut stage_len = len;
+        loop {
+            let stage = dispatches.len();
+            assert!(stage < MAX_STAGES, "too many minmax stages");
+            let args = if stage == 0 {
+                Args::new(start, stride, len)
+            } else {
+                Args::new(0, 1, stage_len)
+            };
+            queue.write_buffer(
+                &self.args,
+                (stage * mem::size_of::<Args>()) as BufferAddress,
+                bytemuck::cast_slice(&[args]),
+            );
+            let src = if stage == 0 {
+                slice.buf()
+            } else {
+                &self.scratch[(stage - 1) % 2]
+            };
+            bind_groups.push(self.bind_group(device, src, &self.scratch[stage % 2]));
+            let n_workgroups = n_workgroups(stage_len);
+            dispatches.push(n_workgroups);
+            stage_len = n_workgroups;
+            if stage_len == 1 {
+                break;
+            }
+        }
+        {
+            let mut cpass =
+                encoder.begin_compute_pass(&wgpu::ComputePassDescriptor { label: None });
+            for (stage, (bind_group, n_workgroups)) in
+                bind_groups.iter().zip(&dispatches).enumerate()
+            {
+                if stage == 0 {
+                    cpass.set_pipeline(&self.strided_pipeline);
+                } else if stage == 1 {
+                    cpass.set_pipeline(&self.pairs_pipeline);
+                }
+                cpass.set_bind_group(
+                    0,
+                    bind_group,
+                    &[(stage * mem::size_of::<Args>()) as DynamicOffset],
+                );
+                cpass.dispatch(*n_workgroups, 1, 1);
+            }
+        }
+        let result = &self.scratch[(dispatches.len() - 1) % 2];
+        encoder.copy_buffer_to_buffer(result, 0, &self.readback, 0, PAIR_SIZE);
+        queue.submit(iter::once(encoder.finish()));
+
+        let slice = self.readback.slice(..);
+        let mapping = slice.map_async(MapMode::Read);
+        // mapping is only resolved when the device is polled
+        device.poll(Maintain::Wait);
+        mapping.await?;
+        let min_max = {
+            let map = slice.get_mapped_range();
+            let pair: &[f32] = bytemuck::cast_slice(&map);
+            (pair[0], pair[1])
+        };
+        self.readback.unmap();
+        Ok(min_max)
+    }
+
+    fn bind_group(&self, device: &Device, src: &wgpu::Buffer, dest: &wgpu::Buffer) -> BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &self.layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::Buffer(BufferBinding {
+                        buffer: &self.args,
+                        offset: 0,
+                        size: NonZeroU64::new(mem::size_of::<Args>() as _),
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: src.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: dest.as_entire_binding(),
+                },
+            ],
+            label: Some("minmax bind group"),
+        })
+    }
+}
+
+fn n_workgroups(len: u32) -> u32 {
+    ((len + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE).clamp(1, MAX_WORKGROUPS)
+}
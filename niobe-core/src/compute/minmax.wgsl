@@ -0,0 +1,102 @@
+[[block]]
+struct Args {
+    // offset of the first element in f32 units
+    offset: u32;
+    // distance between elements in f32 units, used for interleaved data eg. y of a Point2d
+    stride: u32;
+    // number of elements, for `minmax_pairs` number of (min, max) pairs
+    len: u32;
+};
+
+[[block]]
+struct Data {
+    data: array<f32>;
+};
+
+[[group(0), binding(0)]] var<uniform> args: Args;
+[[group(0), binding(1)]] var<storage, read> src: Data;
+[[group(0), binding(2)]] var<storage, read_write> dest: Data;
+// 64 is `WORKGROUP_SIZE` in minmax.rs, it's also used by `workgroup_size` and grid sizes below
+var<workgroup> smin: array<f32, 64>;
+var<workgroup> smax: array<f32, 64>;
+
+// Multi-stage reduction, each work group writes one (min, max) pair into dest at workgroup_id.
+// First stage reads strided elements, every following stage reduces pairs of the previous one
+// until one pair is left. Each thread loops over the grid so that number of work groups can be
+// capped.
+// NaN values are skipped, if there are no values min is f32::MAX and max is f32::MIN.
+// code modified from https://developer.download.nvidia.com/assets/cuda/files/reduction.pdf
+// and from https://github.com/sschaetz/nvidia-opencl-examples/blob/master/OpenCL/src/oclReduction/oclReduction_kernel.cl
+
+fn reduce_workgroup(lid: u32, wgid: u32) {
+    workgroupBarrier();
+    // half of `WORKGROUP_SIZE`
+    var s: u32 = 32u;
+    loop {
+        if (s == 0u) {
+            break;
+        }
+        if (lid < s) {
+            smin[lid] = min(smin[lid], smin[lid + s]);
+            smax[lid] = max(smax[lid], smax[lid + s]);
+        }
+        workgroupBarrier();
+        s = s >> 1u;
+    }
+    if (lid == 0u) {
+        dest.data[wgid * 2u] = smin[0];
+        dest.data[wgid * 2u + 1u] = smax[0];
+    }
+}
+
+[[stage(compute), workgroup_size(64)]]
+fn minmax_strided(
+    [[builtin(global_invocation_id)]] global_invocation_id: vec3<u32>,
+    [[builtin(local_invocation_id)]] local_invocation_id: vec3<u32>,
+    [[builtin(workgroup_id)]] workgroup_id: vec3<u32>,
+    [[builtin(num_workgroups)]] num_workgroups: vec3<u32>,
+) {
+    let grid_size = num_workgroups.x * 64u;
+    var lo: f32 = 3.40282347e+38;
+    var hi: f32 = -3.40282347e+38;
+    var i: u32 = global_invocation_id.x;
+    loop {
+        if (i >= args.len) {
+            break;
+        }
+        let value = src.data[args.offset + i * args.stride];
+        // NaN is not equal to itself
+        if (value == value) {
+            lo = min(lo, value);
+            hi = max(hi, value);
+        }
+        i = i + grid_size;
+    }
+    smin[local_invocation_id.x] = lo;
+    smax[local_invocation_id.x] = hi;
+    reduce_workgroup(local_invocation_id.x, workgroup_id.x);
+}
+
+[[stage(compute), workgroup_size(64)]]
+fn minmax_pairs(
+    [[builtin(global_invocation_id)]] global_invocation_id: vec3<u32>,
+    [[builtin(local_invocation_id)]] local_invocation_id: vec3<u32>,
+    [[builtin(workgroup_id)]] workgroup_id: vec3<u32>,
+    [[builtin(num_workgroups)]] num_workgroups: vec3<u32>,
+) {
+    let grid_size = num_workgroups.x * 64u;
+    var lo: f32 = 3.40282347e+38;
+    var hi: f32 = -3.40282347e+38;
+    var i: u32 = global_invocation_id.x;
+    loop {
+        if (i >= args.len) {
+            break;
+        }
+        lo = min(lo, src.data[i * 2u]);
+        hi = max(hi, src.data[i * 2u + 1u]);
+        i = i + grid_size;
+    }
+    smin[local_invocation_id.x] = lo;
+    smax[local_invocation_id.x] = hi;
+    reduce_workgroup(local_invocation_id.x, workgroup_id.x);
+}
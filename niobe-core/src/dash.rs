@@ -0,0 +1,129 @@
+use crate::buffer::{Buffer, BufferSlice};
+use crate::pipelines::line::LineRenderer;
+use crate::pipelines::AxisScale;
+use crate::Point2d;
+use nalgebra_glm::Vec2;
+use wgpu::{BufferUsages, Device, Queue};
+
+/// Distances in pixels of every vertex from the start of its line, needed by
+/// `LineDrawer::draw_dashed` to keep the dash pattern continuous across segments.
+///
+/// Distances only depend on zoom so panning doesn't require recomputing, appended points only
+/// compute distances for the new points.
+/// Distances are stored in a vertex buffer that can be passed to `LineDrawer::draw_dashed`.
+pub struct DashDistances {
+    /// accumulated in f64 so that long lines don't drift, the buffer gets f32
+    distances: Vec<f64>,
+    buffer: Buffer<f32>,
+    /// pixels per unit and axis scales the distances were measured with
+    measured: Option<(Vec2, AxisScale, AxisScale)>,
+}
+
+impl DashDistances {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            distances: Vec::new(),
+            buffer: Buffer::with_capacity(device, BufferUsages::VERTEX, 1024),
+            measured: None,
+        }
+    }
+
+    /// Must be called when already measured points change so that next update recomputes.
+    pub fn invalidate(&mut self) {
+        self.measured = None;
+    }
+
+    /// Measures `points` of a line drawn with `P`, `pixels_per_unit` is the size of one axis unit
+    /// in pixels along each axis, eg. `scale / pixel_scale`, points are mapped onto axes with
+    /// `x_scale` and `y_scale` first.
+    /// Returns true if distances were recomputed or extended.
+    pub fn update<P: LineRenderer>(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        points: &[Point2d],
+        x_scale: AxisScale,
+        y_scale: AxisScale,
+        pixels_per_unit: Vec2,
+    ) -> bool {
+        let len = self.distances.len();
+        let measured = Some((pixels_per_unit, x_scale, y_scale));
+        if self.measured == measured {
+            if points.len() == len {
+                return false;
+            }
+            if points.len() > len && len % P::LINE_MULTIPLIER as usize == 0 {
+                line_distances(
+                    points,
+                    len,
+                    x_scale,
+                    y_scale,
+                    pixels_per_unit,
+                    P::LINE_MULTIPLIER,
+                    &mut self.distances,
+                );
+                let distances: Vec<_> = self.distances[len..].iter().map(|&x| x as f32).collect();
+                self.buffer.extend(device, queue, &distances);
+                return true;
+            }
+        }
+        self.measured = measured;
+        self.distances.clear();
+        line_distances(
+            points,
+            0,
+            x_scale,
+            y_scale,
+            pixels_per_unit,
+            P::LINE_MULTIPLIER,
+            &mut self.distances,
+        );
+        let distances: Vec<_> = self.distances.iter().map(|&x| x as f32).collect();
+        self.buffer.clear();
+        self.buffer.extend(device, queue, &distances);
+        true
+    }
+
+    pub fn distances(&self) -> &[f64] {
+        &self.distances
+    }
+
+    pub fn slice(&self) -> BufferSlice<'_, f32> {
+        self.buffer.slice(..)
+    }
+}
+
+/// Appends distances in pixels of `points[start..]` to `out` which must contain distances of
+/// points before `start`.
+/// Points are mapped with `x_scale` and `y_scale` before measuring since segments are straight
+/// on screen, not in data.
+/// Line strips (`line_multiplier` 1) accumulate distance over all points, segments of line lists
+/// (`line_multiplier` 2) start at 0.
+/// Segments with NaN points or points that are not on a log axis have no length.
+pub fn line_distances(
+    points: &[Point2d],
+    start: usize,
+    x_scale: AxisScale,
+    y_scale: AxisScale,
+    pixels_per_unit: Vec2,
+    line_multiplier: u32,
+    out: &mut Vec<f64>,
+) {
+    let forward = |p: Point2d| Point2d::new(x_scale.forward(p.x), y_scale.forward(p.y));
+    debug_assert_eq!(out.len(), start);
+    for i in start..points.len() {
+        let line_start = if line_multiplier == 1 {
+            i == 0
+        } else {
+            i % line_multiplier as usize == 0
+        };
+        if line_start {
+            out.push(0.);
+            continue;
+        }
+        let delta = (forward(points[i]) - forward(points[i - 1])).component_mul(&pixels_per_unit);
+        let length = delta.norm();
+        let length = if length.is_finite() { length } else { 0. };
+        out.push(out[i - 1] + length as f64);
+    }
+}
@@ -16,6 +16,9 @@ use stretch::style::Dimension;
 
 pub mod buffer;
 pub mod components;
+pub mod compute;
+pub mod dash;
+pub mod lod;
 pub mod pipelines;
 
 #[rustfmt::skip]
@@ -28,6 +31,123 @@ pub struct Mesh2d<IF: IndexFormat> {
     pub indices: Buffer<IF>,
 }
 
+/// Shape of a scatter marker.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MarkerShape {
+    Circle,
+    Square,
+    Triangle,
+    Diamond,
+    /// Diagonal cross (x).
+    Cross,
+    /// Upright cross (+).
+    Plus,
+}
+
+impl Mesh2d<u16> {
+    /// Unit square centered at origin, used for instanced rectangles.
+    pub fn quad(device: &wgpu::Device) -> Self {
+        let vertices = [
+            Point2d::new(-0.5, -0.5),
+            Point2d::new(0.5, -0.5),
+            Point2d::new(0.5, 0.5),
+            Point2d::new(-0.5, 0.5),
+        ];
+        Self {
+            vertices: Buffer::new(device, wgpu::BufferUsages::VERTEX, &vertices),
+            indices: Buffer::new(device, wgpu::BufferUsages::INDEX, &[0u16, 1, 2, 2, 3, 0]),
+        }
+    }
+
+    /// Marker centered at origin that fits into a unit square, used for instanced scatter points.
+    pub fn marker(device: &wgpu::Device, shape: MarkerShape) -> Self {
+        const CIRCLE_SEGMENTS: u16 = 32;
+        // width of cross and plus arms relative to marker size
+        const ARM: f32 = 0.2;
+        let (vertices, indices): (Vec<Point2d>, Vec<u16>) = match shape {
+            MarkerShape::Circle => {
+                let mut vertices = vec![Point2d::new(0., 0.)];
+                vertices.extend((0..CIRCLE_SEGMENTS).map(|i| {
+                    let angle = i as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+                    Point2d::new(angle.cos() * 0.5, angle.sin() * 0.5)
+                }));
+                let indices = (0..CIRCLE_SEGMENTS)
+                    .flat_map(|i| vec![0, i + 1, (i + 1) % CIRCLE_SEGMENTS + 1])
+                    .collect();
+                (vertices, indices)
+            }
+            MarkerShape::Square => {
+                let vertices = vec![
+                    Point2d::new(-0.5, -0.5),
+                    Point2d::new(0.5, -0.5),
+                    Point2d::new(0.5, 0.5),
+                    Point2d::new(-0.5, 0.5),
+                ];
+                (vertices, vec![0, 1, 2, 2, 3, 0])
+            }
+            MarkerShape::Triangle => {
+                let vertices = vec![
+                    Point2d::new(-0.5, -0.5),
+                    Point2d::new(0.5, -0.5),
+                    Point2d::new(0., 0.5),
+                ];
+                (vertices, vec![0, 1, 2])
+            }
+            MarkerShape::Diamond => {
+                let vertices = vec![
+                    Point2d::new(0., -0.5),
+                    Point2d::new(0.5, 0.),
+                    Point2d::new(0., 0.5),
+                    Point2d::new(-0.5, 0.),
+                ];
+                (vertices, vec![0, 1, 2, 2, 3, 0])
+            }
+            MarkerShape::Cross | MarkerShape::Plus => {
+                let a = ARM / 2.;
+                // outline of the plus, inner corners are 2, 5, 8 and 11
+                let mut vertices = vec![
+                    Point2d::new(-a, -0.5),
+                    Point2d::new(a, -0.5),
+                    Point2d::new(a, -a),
+                    Point2d::new(0.5, -a),
+                    Point2d::new(0.5, a),
+                    Point2d::new(a, a),
+                    Point2d::new(a, 0.5),
+                    Point2d::new(-a, 0.5),
+                    Point2d::new(-a, a),
+                    Point2d::new(-0.5, a),
+                    Point2d::new(-0.5, -a),
+                    Point2d::new(-a, -a),
+                ];
+                if shape == MarkerShape::Cross {
+                    // rotate by 45 degrees, the outer corners of the arms then reach
+                    // (0.5 + a) / sqrt(2) from the center, scale them back onto the unit square
+                    let (sin, cos) = std::f32::consts::FRAC_PI_4.sin_cos();
+                    let scale = 0.5 * std::f32::consts::SQRT_2 / (0.5 + a);
+                    for v in &mut vertices {
+                        *v = Point2d::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos) * scale;
+                    }
+                }
+                #[rustfmt::skip]
+                let indices = vec![
+                    // center
+                    2, 5, 8, 8, 11, 2,
+                    // arms
+                    0, 1, 2, 2, 11, 0,
+                    2, 3, 4, 4, 5, 2,
+                    5, 6, 7, 7, 8, 5,
+                    8, 9, 10, 10, 11, 8,
+                ];
+                (vertices, indices)
+            }
+        };
+        Self {
+            vertices: Buffer::new(device, wgpu::BufferUsages::VERTEX, &vertices),
+            indices: Buffer::new(device, wgpu::BufferUsages::INDEX, &indices),
+        }
+    }
+}
+
 pub trait IndexFormat: Pod {
     const FORMAT: wgpu::IndexFormat;
 }
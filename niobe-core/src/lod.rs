@@ -0,0 +1,141 @@
+use crate::buffer::{Buffer, BufferSlice};
+use crate::Point2d;
+use wgpu::{BufferUsages, Device, Queue};
+
+/// Level of detail for line series with a lot more points than pixels.
+/// Visible x range is split into columns about a pixel wide, for each column only the first, min,
+/// max and last point is kept (M4 aggregation) so there are at most 4 points per column.
+///
+/// Columns are anchored at x = 0 so that panning by less than a column doesn't change the mapping
+/// and doesn't require recomputing. Column width is snapped to one of `WIDTH_STEPS` steps per
+/// power of two so that float noise in the visible range doesn't change it while panning, which
+/// makes columns up to about 1% wider or narrower than a pixel and not aligned with pixels. The
+/// line is therefore not pixel perfect but off by at most a pixel.
+/// Decimated points are stored in a vertex buffer that can be passed to `LineDrawer::draw`.
+pub struct M4Decimator {
+    points: Vec<Point2d>,
+    buffer: Buffer<Point2d>,
+    key: Option<ColumnKey>,
+}
+
+const WIDTH_STEPS: f32 = 64.;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct ColumnKey {
+    columns: u32,
+    /// column width is `2 ^ (width_step / WIDTH_STEPS)`
+    width_step: i32,
+    first_column: i64,
+    last_column: i64,
+}
+
+impl M4Decimator {
+    pub fn new(device: &Device) -> Self {
+        Self {
+            points: Vec::new(),
+            buffer: Buffer::with_capacity(device, BufferUsages::VERTEX, 1024),
+            key: None,
+        }
+    }
+
+    /// Must be called when series data changes so that next update recomputes.
+    pub fn invalidate(&mut self) {
+        self.key = None;
+    }
+
+    /// Decimates `data` which must be sorted by x, `x_min..x_max` is the visible range that is
+    /// drawn into `columns` pixels.
+    /// Returns true if points were recomputed, false if the column mapping hasn't changed.
+    pub fn update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        data: &[Point2d],
+        x_min: f32,
+        x_max: f32,
+        columns: u32,
+    ) -> bool {
+        if columns == 0 || !(x_max > x_min) {
+            return false;
+        }
+        let width_step = (((x_max - x_min) / columns as f32).log2() * WIDTH_STEPS).round() as i32;
+        let column_width = (width_step as f32 / WIDTH_STEPS).exp2();
+        let key = ColumnKey {
+            columns,
+            width_step,
+            first_column: (x_min / column_width).floor() as i64,
+            last_column: (x_max / column_width).floor() as i64,
+        };
+        if self.key == Some(key) {
+            return false;
+        }
+        self.key = Some(key);
+        // whole columns so that panning within the first or last one doesn't drop points
+        let start = key.first_column as f32 * column_width;
+        let end = (key.last_column + 1) as f32 * column_width;
+        m4(data, start, end, column_width, &mut self.points);
+        self.buffer.clear();
+        self.buffer.extend(device, queue, &self.points);
+        true
+    }
+
+    pub fn points(&self) -> &[Point2d] {
+        &self.points
+    }
+
+    pub fn slice(&self) -> BufferSlice<'_, Point2d> {
+        self.buffer.slice(..)
+    }
+}
+
+/// Writes M4 aggregate of `data` in `x_min..x_max` into `out`.
+/// One point on each side outside of the range is included so that lines continue past the view.
+/// Points with NaN y are kept, once for consecutive ones, so that gaps stay gaps.
+pub fn m4(data: &[Point2d], x_min: f32, x_max: f32, column_width: f32, out: &mut Vec<Point2d>) {
+    out.clear();
+    let start = data.partition_point(|p| p.x < x_min).saturating_sub(1);
+    let end = (data.partition_point(|p| p.x <= x_max) + 1).min(data.len());
+    let visible = &data[start..end];
+    let max_columns = ((x_max - x_min) / column_width).ceil() as usize + 2;
+    if visible.len() <= max_columns * 4 {
+        out.extend_from_slice(visible);
+        return;
+    }
+
+    let column_of = |p: &Point2d| (p.x / column_width).floor() as i64;
+    let mut i = 0;
+    while i < visible.len() {
+        if visible[i].y.is_nan() {
+            if out.last().map_or(true, |p| !p.y.is_nan()) {
+                out.push(visible[i]);
+            }
+            i += 1;
+            continue;
+        }
+        // a gap ends the column early, points after it start a new one
+        let column = column_of(&visible[i]);
+        let first = i;
+        let mut last = i;
+        let mut min = i;
+        let mut max = i;
+        i += 1;
+        while i < visible.len() && !visible[i].y.is_nan() && column_of(&visible[i]) == column {
+            if visible[i].y < visible[min].y {
+                min = i;
+            }
+            if visible[i].y > visible[max].y {
+                max = i;
+            }
+            last = i;
+            i += 1;
+        }
+        let (a, b) = if min < max { (min, max) } else { (max, min) };
+        let mut prev = None;
+        for index in [first, a, b, last].iter().copied() {
+            if prev != Some(index) {
+                out.push(visible[index]);
+            }
+            prev = Some(index);
+        }
+    }
+}
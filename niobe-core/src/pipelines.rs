@@ -1,6 +1,12 @@
+use nalgebra_glm::Vec2;
+use std::collections::HashMap;
 use std::ops::{Deref, DerefMut};
-use wgpu::{CommandEncoder, Queue, RenderPass, RenderPipeline};
+use wgpu::{
+    CommandEncoder, Device, Operations, Queue, RenderPass, RenderPassColorAttachment,
+    RenderPipeline, TextureFormat, TextureView,
+};
 
+pub mod area;
 pub mod line;
 pub mod mesh;
 pub mod ui;
@@ -9,3 +15,234 @@ pub struct Drawer<'a> {
     pub pipeline: &'a RenderPipeline,
     pub pass: &'a mut RenderPass<'a>,
 }
+
+/// How fragments of a series are combined with what is already drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// Opaque, alpha is ignored.
+    Replace,
+    /// Regular transparency with straight (not premultiplied) alpha.
+    Alpha,
+    /// Transparency for colors that are already multiplied by their alpha.
+    Premultiplied,
+    /// Adds color weighted by alpha, overlapping points get brighter, eg. for density plots.
+    Additive,
+    /// Multiplies destination by color, colors are expected to be premultiplied.
+    /// Overlapping regions get darker, eg. for shading.
+    Multiply,
+}
+
+impl BlendMode {
+    /// Every blend mode in the order series are drawn in, opaque ones first.
+    pub const ALL: [BlendMode; 5] = [
+        BlendMode::Replace,
+        BlendMode::Alpha,
+        BlendMode::Premultiplied,
+        BlendMode::Additive,
+        BlendMode::Multiply,
+    ];
+
+    pub fn blend_state(self) -> wgpu::BlendState {
+        use wgpu::{BlendComponent, BlendFactor, BlendOperation, BlendState};
+        let over = BlendComponent {
+            src_factor: BlendFactor::One,
+            dst_factor: BlendFactor::OneMinusSrcAlpha,
+            operation: BlendOperation::Add,
+        };
+        match self {
+            BlendMode::Replace => BlendState::REPLACE,
+            BlendMode::Alpha => BlendState::ALPHA_BLENDING,
+            BlendMode::Premultiplied => BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            BlendMode::Additive => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
+            BlendMode::Multiply => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::Dst,
+                    dst_factor: BlendFactor::OneMinusSrcAlpha,
+                    operation: BlendOperation::Add,
+                },
+                alpha: over,
+            },
+        }
+    }
+}
+
+impl Default for BlendMode {
+    fn default() -> Self {
+        BlendMode::Alpha
+    }
+}
+
+/// Mapping of data values onto an axis, series shaders apply it before scale and translate so that
+/// scale and translate of a log axis are in units of the logarithm.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AxisScale {
+    Linear,
+    /// Logarithm with the given base, non positive values are not drawn.
+    Log(f32),
+}
+
+impl AxisScale {
+    pub const LOG10: AxisScale = AxisScale::Log(10.);
+
+    /// Value of `log_base` in series uniforms, 0 for linear axes.
+    pub fn log_base(self) -> f32 {
+        match self {
+            AxisScale::Linear => 0.,
+            AxisScale::Log(base) => base,
+        }
+    }
+
+    /// `log_base` of both axes for series uniforms.
+    pub fn log_bases(x: AxisScale, y: AxisScale) -> Vec2 {
+        Vec2::new(x.log_base(), y.log_base())
+    }
+
+    /// Data value to axis units.
+    pub fn forward(self, value: f32) -> f32 {
+        match self {
+            AxisScale::Linear => value,
+            AxisScale::Log(base) => value.log(base),
+        }
+    }
+
+    /// Axis units to data value.
+    pub fn inverse(self, value: f32) -> f32 {
+        match self {
+            AxisScale::Linear => value,
+            AxisScale::Log(base) => base.powf(value),
+        }
+    }
+}
+
+impl Default for AxisScale {
+    fn default() -> Self {
+        AxisScale::Linear
+    }
+}
+
+/// Pipelines of one kind that are created on first use of a blend mode and reused after.
+pub struct BlendCache<P> {
+    pipelines: HashMap<BlendMode, P>,
+}
+
+impl<P> BlendCache<P> {
+    pub fn new() -> Self {
+        Self {
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// Returns pipeline for `blend`, `create` is only called if there isn't one yet.
+    pub fn get_or_create(&mut self, blend: BlendMode, create: impl FnOnce(BlendMode) -> P) -> &P {
+        self.pipelines.entry(blend).or_insert_with(|| create(blend))
+    }
+
+    pub fn get(&self, blend: BlendMode) -> Option<&P> {
+        self.pipelines.get(&blend)
+    }
+
+    /// Created pipelines in the order of `BlendMode::ALL`.
+    pub fn iter(&self) -> impl Iterator<Item = (BlendMode, &P)> {
+        BlendMode::ALL
+            .iter()
+            .filter_map(move |&blend| Some((blend, self.get(blend)?)))
+    }
+}
+
+impl<P> Default for BlendCache<P> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Multisample state of series pipelines, `sample_count` 1 disables anti-aliasing.
+/// Every pipeline that draws into the same render pass must use the same sample count as the
+/// `MultisampleTarget` of that pass.
+pub fn multisample_state(sample_count: u32) -> wgpu::MultisampleState {
+    wgpu::MultisampleState {
+        count: sample_count,
+        mask: !0,
+        alpha_to_coverage_enabled: false,
+    }
+}
+
+/// Multisampled color attachment that gets resolved into the final view, used for MSAA.
+pub struct MultisampleTarget {
+    view: TextureView,
+    format: TextureFormat,
+    sample_count: u32,
+    size: (u32, u32),
+}
+
+impl MultisampleTarget {
+    pub fn new(
+        device: &Device,
+        format: TextureFormat,
+        sample_count: u32,
+        size: (u32, u32),
+    ) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("niobe-core::MultisampleTarget"),
+            size: wgpu::Extent3d {
+                width: size.0.max(1),
+                height: size.1.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        });
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+            format,
+            sample_count,
+            size,
+        }
+    }
+
+    /// Recreates the texture if the size has changed.
+    pub fn resize(&mut self, device: &Device, size: (u32, u32)) {
+        if self.size != size {
+            *self = Self::new(device, self.format, self.sample_count, size);
+        }
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    /// Attachment that renders into this target and resolves into `resolve_target` at the end of
+    /// the pass. The target is always cleared to `clear_color` since resolving overwrites all of
+    /// `resolve_target` and its contents can't be loaded into the target.
+    pub fn color_attachment<'a>(
+        &'a self,
+        resolve_target: &'a TextureView,
+        clear_color: wgpu::Color,
+    ) -> RenderPassColorAttachment<'a> {
+        RenderPassColorAttachment {
+            view: &self.view,
+            resolve_target: Some(resolve_target),
+            ops: Operations {
+                load: wgpu::LoadOp::Clear(clear_color),
+                store: true,
+            },
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use crate::buffer::BufferSlice;
+use crate::pipelines::{multisample_state, BlendMode};
+use crate::Point2d;
+use bytemuck::{Pod, Zeroable};
+use nalgebra_glm::Vec2;
+use rgb::RGBA;
+use std::mem;
+use std::num::NonZeroU64;
+use wgpu::util::{DeviceExt, RenderEncoder};
+use wgpu::{
+    BindGroup, BindGroupLayout, BindingResource, BufferBinding, Device, DynamicOffset,
+    ShaderModule, TextureFormat,
+};
+
+#[repr(C, align(256))]
+#[derive(Copy, Clone, Debug)]
+pub struct AreaUniform {
+    pub color: RGBA<f32>,
+    pub scale: Vec2,
+    pub translate: Vec2,
+    pub baseline: f32,
+    /// if not 0 area is filled between upper line and `baseline`
+    pub use_baseline: u32,
+    /// see `AxisScale::log_bases`
+    pub log_base: Vec2,
+}
+
+unsafe impl Pod for AreaUniform {}
+unsafe impl Zeroable for AreaUniform {}
+
+pub struct AreaShader {
+    shader: ShaderModule,
+}
+
+impl AreaShader {
+    pub fn new(device: &Device) -> Self {
+        let shader = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: Some("area shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("area.wgsl").into()),
+        });
+        Self { shader }
+    }
+}
+
+pub struct AreaBindGroup {
+    bind_group: BindGroup,
+}
+
+impl AreaBindGroup {
+    pub fn layout(device: &Device) -> BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+            label: Some("area pipeline uniform bind group"),
+        })
+    }
+
+    pub fn new(device: &Device, slice: &BufferSlice<'_, AreaUniform>) -> Self {
+        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &Self::layout(device),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::Buffer(BufferBinding {
+                    buffer: &slice.buf(),
+                    offset: slice.raw_addres_range().start,
+                    size: Some(NonZeroU64::new(std::mem::size_of::<AreaUniform>() as _).unwrap()),
+                }),
+            }],
+            label: Some("uniform area group"),
+        });
+        Self {
+            bind_group: uniform_bind_group,
+        }
+    }
+}
+
+/// Fills area between two lines or between a line and a constant baseline.
+/// Every segment is an instance so that streamed data only needs new instances and NaN values
+/// leave gaps without retessellating.
+pub struct AreaPipeline {
+    pipeline: wgpu::RenderPipeline,
+    fan_vbo: wgpu::Buffer,
+}
+
+impl AreaPipeline {
+    pub fn new(
+        device: &Device,
+        format: TextureFormat,
+        sample_count: u32,
+        blend: BlendMode,
+        area_shader: &AreaShader,
+    ) -> Self {
+        let fan_vbo = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("area fan vbo"),
+            contents: bytemuck::cast_slice(&FAN),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("area render pipeline layout"),
+                bind_group_layouts: &[&AreaBindGroup::layout(device)],
+                push_constant_ranges: &[],
+            });
+        let buffers = [
+            wgpu::VertexBufferLayout {
+                array_stride: mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &[wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                }],
+            },
+            wgpu::VertexBufferLayout {
+                array_stride: mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &[wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 1,
+                    format: wgpu::VertexFormat::Float32x2,
+                }],
+            },
+            wgpu::VertexBufferLayout {
+                array_stride: mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &[wgpu::VertexAttribute {
+                    offset: mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x2,
+                }],
+            },
+            wgpu::VertexBufferLayout {
+                array_stride: mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &[wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x2,
+                }],
+            },
+            wgpu::VertexBufferLayout {
+                array_stride: mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes: &[wgpu::VertexAttribute {
+                    offset: mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32x2,
+                }],
+            },
+        ];
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("area render pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &area_shader.shader,
+                entry_point: "main",
+                buffers: &buffers,
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &area_shader.shader,
+                entry_point: "main",
+                targets: &[wgpu::ColorTargetState {
+                    format,
+                    blend: Some(blend.blend_state()),
+                    write_mask: wgpu::ColorWrites::ALL,
+                }],
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                clamp_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: multisample_state(sample_count),
+        });
+        Self {
+            pipeline: render_pipeline,
+            fan_vbo,
+        }
+    }
+
+    pub fn drawer<'s, 'e, E: RenderEncoder<'s>>(
+        &'s self,
+        encoder: &'e mut E,
+    ) -> AreaDrawer<'s, 'e, E> {
+        encoder.set_pipeline(&self.pipeline);
+        encoder.set_vertex_buffer(0, self.fan_vbo.slice(..));
+        AreaDrawer {
+            encoder,
+            pipeline: self,
+        }
+    }
+}
+
+#[derive(AsMut)]
+pub struct AreaDrawer<'s, 'e, E> {
+    #[as_mut]
+    encoder: &'e mut E,
+    pipeline: &'s AreaPipeline,
+}
+
+impl<'s, 'e, E: RenderEncoder<'s>> AreaDrawer<'s, 'e, E> {
+    pub fn set_bind_group(&mut self, bind_group: &'s AreaBindGroup, uniform_id: u32) -> &mut Self {
+        self.encoder.set_bind_group(
+            0,
+            &bind_group.bind_group,
+            &[uniform_id * std::mem::size_of::<AreaUniform>() as DynamicOffset],
+        );
+        self
+    }
+
+    /// Fills between `upper` and `lower`, both must have the same number of points.
+    /// If `lower` is `None` uniform must use baseline.
+    pub fn draw(
+        &mut self,
+        upper: BufferSlice<'s, Point2d>,
+        lower: Option<BufferSlice<'s, Point2d>>,
+    ) -> &mut Self {
+        let lower = lower.unwrap_or_else(|| upper.clone());
+        debug_assert_eq!(upper.len(), lower.len());
+        if upper.len() < 2 || lower.len() < 2 {
+            return self;
+        }
+        self.encoder.set_vertex_buffer(1, upper.to_raw_slice());
+        self.encoder.set_vertex_buffer(2, upper.to_raw_slice());
+        self.encoder.set_vertex_buffer(3, lower.to_raw_slice());
+        self.encoder.set_vertex_buffer(4, lower.to_raw_slice());
+        // Since instance vertex buffers are sliced we start from 0
+        self.encoder
+            .draw(0..FAN.len() as u32, 0..upper.len().min(lower.len()) - 1);
+        self
+    }
+}
+
+/// Fan of 4 triangles around the center of a segment, see `area.wgsl`.
+const FAN: [[f32; 2]; 12] = [
+    [0., 0.],
+    [0., 1.],
+    [0.5, 0.5],
+    [0., 1.],
+    [1., 1.],
+    [0.5, 0.5],
+    [1., 1.],
+    [1., 0.],
+    [0.5, 0.5],
+    [1., 0.],
+    [0., 0.],
+    [0.5, 0.5],
+];
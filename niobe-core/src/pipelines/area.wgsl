@@ -0,0 +1,113 @@
+[[block]]
+struct Uniform {
+    color: vec4<f32>;
+    scale: vec2<f32>;
+    translate: vec2<f32>;
+    baseline: f32;
+    // if not 0 lower line is a constant baseline
+    use_baseline: u32;
+    log_base: vec2<f32>;
+};
+
+[[group(0), binding(0)]]
+var<uniform> uni: Uniform;
+
+struct VertexInput {
+    // corner of a segment, x selects first or second point, y selects lower or upper line
+    // (0.5, 0.5) is the center of a triangle fan
+    [[location(0)]] corner: vec2<f32>;
+};
+
+struct InstanceInput {
+    [[location(1)]] upper_first: vec2<f32>;
+    [[location(2)]] upper_second: vec2<f32>;
+    [[location(3)]] lower_first: vec2<f32>;
+    [[location(4)]] lower_second: vec2<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+};
+
+// Logarithm with base `log_base` on axes where it isn't 0.
+fn to_axis(p: vec2<f32>) -> vec2<f32> {
+    var out: vec2<f32> = p;
+    if (uni.log_base.x != 0.0) {
+        out.x = log(p.x) / log(uni.log_base.x);
+    }
+    if (uni.log_base.y != 0.0) {
+        out.y = log(p.y) / log(uni.log_base.y);
+    }
+    return out;
+}
+
+// Raises values below the bottom or left edge of the view on log axes to that edge, so that a
+// baseline at 0 whose logarithm is -inf still spans the visible part.
+fn clamp_to_view(p: vec2<f32>) -> vec2<f32> {
+    let first = (vec2<f32>(-1.0, -1.0) - uni.translate) / uni.scale;
+    let second = (vec2<f32>(1.0, 1.0) - uni.translate) / uni.scale;
+    let lowest = min(first, second);
+    var out: vec2<f32> = p;
+    if (uni.log_base.x != 0.0) {
+        out.x = max(p.x, lowest.x);
+    }
+    if (uni.log_base.y != 0.0) {
+        out.y = max(p.y, lowest.y);
+    }
+    return out;
+}
+
+fn is_nan(x: f32) -> bool {
+    return !(x == x);
+}
+
+// Every instance fills area between one segment of upper and lower line with a fan of 4 triangles.
+// Fan center is the crossing point if lines cross within a segment so that both halves are
+// filled, otherwise it's the center of the trapezoid.
+// Segments that touch a NaN value are collapsed which creates gaps in the area.
+[[stage(vertex)]]
+fn main(model: VertexInput, instance: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+    var lower_first: vec2<f32> = instance.lower_first;
+    var lower_second: vec2<f32> = instance.lower_second;
+    if (uni.use_baseline != 0u) {
+        lower_first = vec2<f32>(instance.upper_first.x, uni.baseline);
+        lower_second = vec2<f32>(instance.upper_second.x, uni.baseline);
+    }
+    // crossing is found in axis units so that it matches drawn lines on log axes
+    let upper_first = to_axis(instance.upper_first);
+    let upper_second = to_axis(instance.upper_second);
+    lower_first = to_axis(lower_first);
+    lower_second = to_axis(lower_second);
+    if (uni.use_baseline != 0u) {
+        lower_first = clamp_to_view(lower_first);
+        lower_second = clamp_to_view(lower_second);
+    }
+    if (is_nan(upper_first.y) || is_nan(upper_second.y)
+        || is_nan(lower_first.y) || is_nan(lower_second.y)) {
+        out.clip_position = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+        return out;
+    }
+    var pos: vec2<f32>;
+    if (model.corner.x == 0.5) {
+        let d0 = upper_first.y - lower_first.y;
+        let d1 = upper_second.y - lower_second.y;
+        if (d0 * d1 < 0.0) {
+            let t = d0 / (d0 - d1);
+            pos = mix(upper_first, upper_second, vec2<f32>(t, t));
+        } else {
+            pos = (upper_first + upper_second + lower_first + lower_second) * 0.25;
+        }
+    } else {
+        let first = mix(lower_first, upper_first, vec2<f32>(model.corner.y, model.corner.y));
+        let second = mix(lower_second, upper_second, vec2<f32>(model.corner.y, model.corner.y));
+        pos = mix(first, second, vec2<f32>(model.corner.x, model.corner.x));
+    }
+    out.clip_position = vec4<f32>(pos * uni.scale + uni.translate, 1.0, 1.0);
+    return out;
+}
+
+[[stage(fragment)]]
+fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return uni.color;
+}
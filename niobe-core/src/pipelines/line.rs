@@ -1,4 +1,5 @@
-use crate::buffer::{Buffer, BufferSlice};
+use crate::buffer::{Buffer, BufferSlice, RingBuffer};
+use crate::pipelines::{multisample_state, BlendMode};
 use crate::Point2d;
 use bytemuck::{Pod, Zeroable};
 use nalgebra_glm::Vec2;
@@ -8,13 +9,17 @@ use std::convert::TryInto;
 use std::marker::PhantomData;
 use std::mem;
 use std::num::NonZeroU64;
+use std::ops::{Range, RangeBounds};
 use wgpu::util::{DeviceExt, RenderEncoder};
 use wgpu::{
     BindGroup, BindGroupLayout, BindingResource, BufferAddress, BufferBinding, Device,
     DynamicOffset, RenderPass, RenderPipeline, ShaderLocation, ShaderModule, SurfaceConfiguration,
-    TextureFormat,
+    TextureFormat, TextureView,
 };
 
+/// Maximum number of entries in a dash array.
+pub const MAX_DASHES: usize = 8;
+
 #[repr(C, align(256))]
 #[derive(Copy, Clone, Debug)]
 pub struct LineUniform {
@@ -22,11 +27,215 @@ pub struct LineUniform {
     pub scale: Vec2,
     pub translate: Vec2,
     pub line_scale: Vec2,
+    /// `LineJoin` as u32
+    pub join: u32,
+    /// `LineCap` as u32
+    pub cap: u32,
+    /// ratio of miter length to line width over which miter joins become bevel joins
+    pub miter_limit: f32,
+    /// number of used entries in `dash`, 0 for solid lines
+    pub dash_count: u32,
+    pub dash_offset: f32,
+    /// sum of used entries in `dash`
+    pub dash_length: f32,
+    /// alternating lengths of dashes and gaps in pixels
+    pub dash: [f32; MAX_DASHES],
+    /// value that maps to the start of the colormap with `ColorSource::Values`
+    pub value_min: f32,
+    /// value that maps to the end of the colormap with `ColorSource::Values`
+    pub value_max: f32,
+    /// see `AxisScale::log_bases`
+    pub log_base: Vec2,
 }
 
 unsafe impl Pod for LineUniform {}
 unsafe impl Zeroable for LineUniform {}
 
+impl LineUniform {
+    pub fn new(color: RGBA<f32>, style: &LineStyle) -> Self {
+        let mut uniform = Self {
+            color,
+            scale: Vec2::new(1., 1.),
+            translate: Vec2::new(0., 0.),
+            line_scale: Vec2::new(0.01, 0.01),
+            value_max: 1.,
+            ..Zeroable::zeroed()
+        };
+        uniform.set_style(style);
+        uniform
+    }
+
+    pub fn set_style(&mut self, style: &LineStyle) {
+        self.join = style.join as u32;
+        self.cap = style.cap as u32;
+        self.miter_limit = style.miter_limit;
+        self.dash = [0.; MAX_DASHES];
+        self.dash_count = 0;
+        self.dash_offset = 0.;
+        self.dash_length = 0.;
+        if let Some(dash) = &style.dash {
+            let pattern = dash.pattern();
+            debug_assert!(pattern.len() <= MAX_DASHES);
+            for (dst, src) in self.dash.iter_mut().zip(&pattern) {
+                *dst = *src;
+                self.dash_count += 1;
+                self.dash_length += *src;
+            }
+            self.dash_offset = dash.offset;
+        }
+    }
+}
+
+/// Shape of a corner between two segments of a line strip.
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineJoin {
+    Miter = 0,
+    Round = 1,
+    Bevel = 2,
+}
+
+/// Shape of the ends of a line.
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineCap {
+    /// Line ends exactly at the end point.
+    Butt = 0,
+    /// Line is extended by half of its width.
+    Square = 1,
+    Round = 2,
+}
+
+/// Dash pattern, dashes always have butt ends.
+/// Only used by `LineDrawer::draw_dashed`, lines drawn with `LineDrawer::draw` are solid.
+#[derive(Clone, Debug, Default)]
+pub struct LineDash {
+    /// alternating lengths of dashes and gaps in pixels, odd arrays are repeated twice
+    pub array: Vec<f32>,
+    /// distance in pixels into the pattern at the start of a line
+    pub offset: f32,
+}
+
+impl LineDash {
+    /// Dots as long as the line is wide.
+    pub fn dotted(width: f32) -> Self {
+        Self {
+            array: vec![width, width * 2.],
+            offset: 0.,
+        }
+    }
+
+    pub fn dashed(width: f32) -> Self {
+        Self {
+            array: vec![width * 6., width * 4.],
+            offset: 0.,
+        }
+    }
+
+    pub fn dash_dot(width: f32) -> Self {
+        Self {
+            array: vec![width * 6., width * 3., width, width * 3.],
+            offset: 0.,
+        }
+    }
+
+    /// `array` with odd arrays repeated twice so that dashes and gaps alternate.
+    pub fn pattern(&self) -> Vec<f32> {
+        let repeat = if self.array.len() % 2 == 1 { 2 } else { 1 };
+        let len = self.array.len() * repeat;
+        self.array.iter().cycle().take(len).copied().collect()
+    }
+
+    /// Dashes of a line that is `length` pixels long as ranges of distance from its start, for
+    /// lines that are drawn on the cpu. Matches `in_dash` of `line.wgsl`.
+    pub fn dashes(&self, length: f32) -> Vec<Range<f32>> {
+        let pattern = self.pattern();
+        let period: f32 = pattern.iter().sum();
+        if !(period > 0.) {
+            return vec![0.0..length];
+        }
+        let mut dashes = Vec::new();
+        let mut distance = -self.offset.rem_euclid(period);
+        for (i, entry) in pattern.iter().enumerate().cycle() {
+            if distance >= length {
+                break;
+            }
+            let next = distance + entry;
+            // even entries are dashes and odd ones gaps
+            if i % 2 == 0 && next > 0. {
+                dashes.push(distance.max(0.)..next.min(length));
+            }
+            distance = next;
+        }
+        dashes
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct LineStyle {
+    pub join: LineJoin,
+    pub cap: LineCap,
+    pub miter_limit: f32,
+    pub dash: Option<LineDash>,
+}
+
+impl Default for LineStyle {
+    fn default() -> Self {
+        Self {
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.,
+            dash: None,
+        }
+    }
+}
+
+/// Where the color of a line comes from, every source needs its own pipeline.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ColorSource {
+    /// `LineUniform::color`
+    Uniform,
+    /// `RGBA<f32>` for every vertex, colors are interpolated along segments
+    Colors,
+    /// `f32` for every vertex that is mapped through a colormap, see `ColormapBindGroup`
+    Values,
+}
+
+/// Per vertex colors of a line, must match `ColorSource` of the pipeline.
+#[derive(Clone)]
+pub enum ColorStream<'a> {
+    Colors(BufferSlice<'a, RGBA<f32>>),
+    Values(BufferSlice<'a, f32>),
+}
+
+impl<'a> ColorStream<'a> {
+    pub fn len(&self) -> u32 {
+        match self {
+            ColorStream::Colors(x) => x.len(),
+            ColorStream::Values(x) => x.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `range` is relative to the start of this stream.
+    pub fn slice(&self, range: impl RangeBounds<u32>) -> Self {
+        match self {
+            ColorStream::Colors(x) => ColorStream::Colors(x.slice(range)),
+            ColorStream::Values(x) => ColorStream::Values(x.slice(range)),
+        }
+    }
+
+    pub fn to_raw_slice(&self) -> wgpu::BufferSlice<'a> {
+        match self {
+            ColorStream::Colors(x) => x.to_raw_slice(),
+            ColorStream::Values(x) => x.to_raw_slice(),
+        }
+    }
+}
+
 pub struct LineShader {
     shader: ShaderModule,
 }
@@ -81,20 +290,134 @@ impl LineBindGroup {
     }
 }
 
+/// 1D texture that values of `ColorSource::Values` are mapped through, bound as group 1.
+/// Values are normalized with `LineUniform::value_min` and `LineUniform::value_max` and clamped,
+/// the nearest texel is used. See `Colormap::create_texture`.
+pub struct ColormapBindGroup {
+    bind_group: BindGroup,
+}
+
+impl ColormapBindGroup {
+    pub fn layout(device: &Device) -> BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                    view_dimension: wgpu::TextureViewDimension::D1,
+                    multisampled: false,
+                },
+                count: None,
+            }],
+            label: Some("line pipeline colormap bind group"),
+        })
+    }
+
+    pub fn new(device: &Device, colormap: &TextureView) -> Self {
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &Self::layout(device),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: BindingResource::TextureView(colormap),
+            }],
+            label: Some("colormap line group"),
+        });
+        Self { bind_group }
+    }
+}
+
 pub struct LinePipeline {
+    color_source: ColorSource,
     pipeline: wgpu::RenderPipeline,
+    dashed_pipeline: wgpu::RenderPipeline,
+    start_cap_pipeline: wgpu::RenderPipeline,
+    end_cap_pipeline: wgpu::RenderPipeline,
     segment_vbo: wgpu::Buffer,
+    fan_vbo: wgpu::Buffer,
 }
 
 impl LinePipeline {
-    pub fn new(device: &Device, format: TextureFormat, line_storage: &LineShader) -> Self {
-        let pipeline = create_pipeline(&device, format, line_storage, Self::LINE_MULTIPLIER as u64);
+    pub fn new(
+        device: &Device,
+        format: TextureFormat,
+        sample_count: u32,
+        blend: BlendMode,
+        line_storage: &LineShader,
+    ) -> Self {
+        Self::with_color_source(
+            device,
+            format,
+            sample_count,
+            blend,
+            line_storage,
+            ColorSource::Uniform,
+        )
+    }
+
+    pub fn with_color_source(
+        device: &Device,
+        format: TextureFormat,
+        sample_count: u32,
+        blend: BlendMode,
+        line_storage: &LineShader,
+        color_source: ColorSource,
+    ) -> Self {
+        let m = Self::LINE_MULTIPLIER as BufferAddress;
+        let segment = [point_attribute(1, 0, m), point_attribute(2, POINT_SIZE, m)];
+        let distances = [distance_attribute(3, 0, m), distance_attribute(4, 1, m)];
+        let colors = color_attributes(color_source, &[(5, 0), (6, 1)], m);
+        // caps read the same buffers as segments, offsets select the end
+        let start_cap = [point_attribute(1, 0, m), point_attribute(2, POINT_SIZE, m)];
+        let end_cap = [point_attribute(1, POINT_SIZE, m), point_attribute(2, 0, m)];
+        let create = |kind, dashed, instances: &[Vec<InstanceBuffer>]| {
+            create_pipeline(
+                device,
+                format,
+                sample_count,
+                blend,
+                line_storage,
+                PipelineKind {
+                    kind,
+                    color_source,
+                    dashed,
+                },
+                &instances.concat(),
+            )
+        };
         Self {
-            pipeline: pipeline.1,
-            segment_vbo: pipeline.0,
+            color_source,
+            pipeline: create("", false, &[separate(&segment), separate(&colors)]),
+            dashed_pipeline: create(
+                "",
+                true,
+                &[separate(&segment), separate(&distances), separate(&colors)],
+            ),
+            start_cap_pipeline: create(
+                "_cap",
+                false,
+                &[
+                    separate(&start_cap),
+                    separate(&color_attributes(color_source, &[(3, 0)], m)),
+                ],
+            ),
+            end_cap_pipeline: create(
+                "_cap",
+                false,
+                &[
+                    separate(&end_cap),
+                    separate(&color_attributes(color_source, &[(3, 1)], m)),
+                ],
+            ),
+            segment_vbo: create_vbo(device, "line segment vbo", &SEGMENT),
+            fan_vbo: create_vbo(device, "line fan vbo", &fan()),
         }
     }
 
+    pub fn color_source(&self) -> ColorSource {
+        self.color_source
+    }
+
     pub fn drawer<'s, 'e, E: RenderEncoder<'s>>(
         &'s self,
         encoder: &'e mut E,
@@ -104,6 +427,7 @@ impl LinePipeline {
         LineDrawer {
             encoder,
             pipeline: self,
+            cap: LineCap::Butt,
         }
     }
 }
@@ -112,20 +436,157 @@ impl LineRenderer for LinePipeline {
     const LINE_MULTIPLIER: u32 = 2;
 }
 
+impl Sealed for LinePipeline {
+    fn draw<'s, E: RenderEncoder<'s>>(
+        &'s self,
+        encoder: &mut E,
+        vertices: BufferSlice<'s, Point2d>,
+        distances: Option<BufferSlice<'s, f32>>,
+        colors: Option<ColorStream<'s>>,
+        cap: LineCap,
+    ) {
+        debug_assert_eq!(colors.is_some(), self.color_source != ColorSource::Uniform);
+        let count = vertices.len() / Self::LINE_MULTIPLIER;
+        if count == 0 {
+            return;
+        }
+        encoder.set_vertex_buffer(0, self.segment_vbo.slice(..));
+        encoder.set_vertex_buffer(1, vertices.to_raw_slice());
+        encoder.set_vertex_buffer(2, vertices.to_raw_slice());
+        // colors are in the slots after distances
+        let mut color_slot = 3;
+        if let Some(distances) = distances {
+            debug_assert_eq!(distances.len(), vertices.len());
+            encoder.set_pipeline(&self.dashed_pipeline);
+            encoder.set_vertex_buffer(3, distances.to_raw_slice());
+            encoder.set_vertex_buffer(4, distances.to_raw_slice());
+            color_slot = 5;
+        } else {
+            encoder.set_pipeline(&self.pipeline);
+        }
+        if let Some(colors) = &colors {
+            debug_assert_eq!(colors.len(), vertices.len());
+            encoder.set_vertex_buffer(color_slot, colors.to_raw_slice());
+            encoder.set_vertex_buffer(color_slot + 1, colors.to_raw_slice());
+        }
+        // Since instance wertex buffers are sliced we start from 0
+        encoder.draw(0..SEGMENT.len() as _, 0..count);
+        // butt caps are empty fans
+        if cap == LineCap::Butt {
+            return;
+        }
+        // every segment has a cap on both ends, vertices are still bound in slots 1 and 2
+        encoder.set_vertex_buffer(0, self.fan_vbo.slice(..));
+        if let Some(colors) = &colors {
+            encoder.set_vertex_buffer(3, colors.to_raw_slice());
+        }
+        encoder.set_pipeline(&self.start_cap_pipeline);
+        encoder.draw(0..FAN_VERTICES, 0..count);
+        encoder.set_pipeline(&self.end_cap_pipeline);
+        encoder.draw(0..FAN_VERTICES, 0..count);
+    }
+}
+
 pub struct LineStripPipeline {
+    color_source: ColorSource,
     pipeline: wgpu::RenderPipeline,
+    dashed_pipeline: wgpu::RenderPipeline,
+    join_pipeline: wgpu::RenderPipeline,
+    dashed_join_pipeline: wgpu::RenderPipeline,
+    cap_pipeline: wgpu::RenderPipeline,
     segment_vbo: wgpu::Buffer,
+    fan_vbo: wgpu::Buffer,
 }
 
 impl LineStripPipeline {
-    pub fn new(device: &Device, format: TextureFormat, line_storage: &LineShader) -> Self {
-        let pipeline = create_pipeline(&device, format, line_storage, Self::LINE_MULTIPLIER as u64);
+    pub fn new(
+        device: &Device,
+        format: TextureFormat,
+        sample_count: u32,
+        blend: BlendMode,
+        line_storage: &LineShader,
+    ) -> Self {
+        Self::with_color_source(
+            device,
+            format,
+            sample_count,
+            blend,
+            line_storage,
+            ColorSource::Uniform,
+        )
+    }
+
+    pub fn with_color_source(
+        device: &Device,
+        format: TextureFormat,
+        sample_count: u32,
+        blend: BlendMode,
+        line_storage: &LineShader,
+        color_source: ColorSource,
+    ) -> Self {
+        // ends of a segment share a slot so that there is room for the neighbors, which are bound
+        // separately at the ends of the strip
+        let segment = shared(&[point_attribute(1, 0, 1), point_attribute(2, POINT_SIZE, 1)]);
+        let neighbors = separate(&[point_attribute(7, 0, 1), point_attribute(8, 0, 1)]);
+        let distances = shared(&[distance_attribute(3, 0, 1), distance_attribute(4, 1, 1)]);
+        let colors = shared(&color_attributes(color_source, &[(5, 0), (6, 1)], 1));
+        let join = [
+            point_attribute(1, 0, 1),
+            point_attribute(2, 0, 1),
+            point_attribute(3, 0, 1),
+        ];
+        let join_distance = [distance_attribute(4, 0, 1)];
+        let join_color = color_attributes(color_source, &[(5, 0)], 1);
+        let create = |kind, dashed, instances: &[Vec<InstanceBuffer>]| {
+            create_pipeline(
+                device,
+                format,
+                sample_count,
+                blend,
+                line_storage,
+                PipelineKind {
+                    kind,
+                    color_source,
+                    dashed,
+                },
+                &instances.concat(),
+            )
+        };
         Self {
-            pipeline: pipeline.1,
-            segment_vbo: pipeline.0,
+            color_source,
+            pipeline: create(
+                "_strip",
+                false,
+                &[segment.clone(), neighbors.clone(), colors.clone()],
+            ),
+            dashed_pipeline: create("_strip", true, &[segment, neighbors, distances, colors]),
+            join_pipeline: create("_join", false, &[separate(&join), separate(&join_color)]),
+            dashed_join_pipeline: create(
+                "_join",
+                true,
+                &[
+                    separate(&join),
+                    separate(&join_distance),
+                    separate(&join_color),
+                ],
+            ),
+            cap_pipeline: create(
+                "_cap",
+                false,
+                &[
+                    separate(&join[..2]),
+                    separate(&color_attributes(color_source, &[(3, 0)], 1)),
+                ],
+            ),
+            segment_vbo: create_vbo(device, "line segment vbo", &SEGMENT),
+            fan_vbo: create_vbo(device, "line fan vbo", &fan()),
         }
     }
 
+    pub fn color_source(&self) -> ColorSource {
+        self.color_source
+    }
+
     pub fn drawer<'s, 'e, E: RenderEncoder<'s>>(
         &'s self,
         encoder: &'e mut E,
@@ -135,6 +596,7 @@ impl LineStripPipeline {
         LineDrawer {
             encoder,
             pipeline: self,
+            cap: LineCap::Butt,
         }
     }
 }
@@ -143,6 +605,94 @@ impl LineRenderer for LineStripPipeline {
     const LINE_MULTIPLIER: u32 = 1;
 }
 
+impl Sealed for LineStripPipeline {
+    fn draw<'s, E: RenderEncoder<'s>>(
+        &'s self,
+        encoder: &mut E,
+        vertices: BufferSlice<'s, Point2d>,
+        distances: Option<BufferSlice<'s, f32>>,
+        colors: Option<ColorStream<'s>>,
+        cap: LineCap,
+    ) {
+        debug_assert_eq!(colors.is_some(), self.color_source != ColorSource::Uniform);
+        let len = vertices.len();
+        if len < 2 {
+            return;
+        }
+        encoder.set_vertex_buffer(0, self.segment_vbo.slice(..));
+        if distances.is_some() {
+            encoder.set_pipeline(&self.dashed_pipeline);
+        } else {
+            encoder.set_pipeline(&self.pipeline);
+        }
+        // Segments from `start` with neighbors from `prev` and `next`, colors are in the slot
+        // after distances. A neighbor is bound to the end point itself at the ends of the strip.
+        let draw_segments = |encoder: &mut E, start: u32, count: u32, prev: u32, next: u32| {
+            encoder.set_vertex_buffer(1, vertices.slice(start..).to_raw_slice());
+            encoder.set_vertex_buffer(2, vertices.slice(prev..).to_raw_slice());
+            encoder.set_vertex_buffer(3, vertices.slice(next..).to_raw_slice());
+            let mut color_slot = 4;
+            if let Some(distances) = &distances {
+                debug_assert_eq!(distances.len(), len);
+                encoder.set_vertex_buffer(4, distances.slice(start..).to_raw_slice());
+                color_slot = 5;
+            }
+            if let Some(colors) = &colors {
+                debug_assert_eq!(colors.len(), len);
+                encoder.set_vertex_buffer(color_slot, colors.slice(start..).to_raw_slice());
+            }
+            // Since instance wertex buffers are sliced we start from 0
+            encoder.draw(0..SEGMENT.len() as _, 0..count);
+        };
+        if len == 2 {
+            draw_segments(encoder, 0, 1, 0, 1);
+        } else {
+            draw_segments(encoder, 0, 1, 0, 2);
+            if len > 3 {
+                draw_segments(encoder, 1, len - 3, 0, 3);
+            }
+            draw_segments(encoder, len - 2, 1, len - 3, len - 1);
+        }
+        encoder.set_vertex_buffer(0, self.fan_vbo.slice(..));
+        // joins fill corners at inner points between ends of segments, with dashes only if the
+        // point is in a dash
+        if len > 2 {
+            let mut color_slot = 4;
+            if let Some(distances) = &distances {
+                encoder.set_pipeline(&self.dashed_join_pipeline);
+                encoder.set_vertex_buffer(4, distances.slice(1..).to_raw_slice());
+                color_slot = 5;
+            } else {
+                encoder.set_pipeline(&self.join_pipeline);
+            }
+            encoder.set_vertex_buffer(1, vertices.to_raw_slice());
+            encoder.set_vertex_buffer(2, vertices.slice(1..).to_raw_slice());
+            encoder.set_vertex_buffer(3, vertices.slice(2..).to_raw_slice());
+            if let Some(colors) = &colors {
+                encoder.set_vertex_buffer(color_slot, colors.slice(1..).to_raw_slice());
+            }
+            encoder.draw(0..FAN_VERTICES, 0..len - 2);
+        }
+        // butt caps are empty fans
+        if cap == LineCap::Butt {
+            return;
+        }
+        encoder.set_pipeline(&self.cap_pipeline);
+        encoder.set_vertex_buffer(1, vertices.slice(0..1).to_raw_slice());
+        encoder.set_vertex_buffer(2, vertices.slice(1..2).to_raw_slice());
+        if let Some(colors) = &colors {
+            encoder.set_vertex_buffer(3, colors.slice(0..1).to_raw_slice());
+        }
+        encoder.draw(0..FAN_VERTICES, 0..1);
+        encoder.set_vertex_buffer(1, vertices.slice(len - 1..).to_raw_slice());
+        encoder.set_vertex_buffer(2, vertices.slice(len - 2..len - 1).to_raw_slice());
+        if let Some(colors) = &colors {
+            encoder.set_vertex_buffer(3, colors.slice(len - 1..).to_raw_slice());
+        }
+        encoder.draw(0..FAN_VERTICES, 0..1);
+    }
+}
+
 #[derive(AsMut)]
 pub struct LineGroupDrawer<'s, 'e, E, P> {
     #[as_mut]
@@ -161,6 +711,7 @@ pub struct LineDrawer<'s, 'e, E, P> {
     #[as_mut]
     pipeline: &'s P,
     encoder: &'e mut E,
+    cap: LineCap,
 }
 
 impl<'s, 'e, E: RenderEncoder<'s>, P: LineRenderer> LineDrawer<'s, 'e, E, P> {
@@ -173,18 +724,66 @@ impl<'s, 'e, E: RenderEncoder<'s>, P: LineRenderer> LineDrawer<'s, 'e, E, P> {
         self
     }
 
+    /// Must match `LineStyle::cap` of the bound `LineUniform`, the drawer can't read it back.
+    /// Caps are not drawn for `LineCap::Butt` which is the default.
+    pub fn set_cap(&mut self, cap: LineCap) -> &mut Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Draws segments followed by joins and caps, which is why this always sets its own pipelines.
     pub fn draw(&mut self, vertices: BufferSlice<'s, Point2d>) -> &mut Self {
-        self.encoder.set_vertex_buffer(1, vertices.to_raw_slice());
-        self.encoder.set_vertex_buffer(2, vertices.to_raw_slice());
-        let range = vertices.range();
-        let mut count = range.end - range.start;
-        if P::LINE_MULTIPLIER == 1 {
-            count -= 1;
+        self.pipeline
+            .draw(self.encoder, vertices, None, None, self.cap);
+        self
+    }
+
+    /// Draws with the dash pattern of the uniform, `distances` are distances in pixels of every
+    /// vertex from the start of the line, see `DashDistances`.
+    pub fn draw_dashed(
+        &mut self,
+        vertices: BufferSlice<'s, Point2d>,
+        distances: BufferSlice<'s, f32>,
+    ) -> &mut Self {
+        self.pipeline
+            .draw(self.encoder, vertices, Some(distances), None, self.cap);
+        self
+    }
+
+    /// Draws with a pipeline created for `ColorSource::Colors` or `ColorSource::Values`,
+    /// `colors` has an entry for every vertex.
+    /// `distances` are needed for dashed lines, see `draw_dashed`.
+    pub fn draw_colored(
+        &mut self,
+        vertices: BufferSlice<'s, Point2d>,
+        colors: ColorStream<'s>,
+        distances: Option<BufferSlice<'s, f32>>,
+    ) -> &mut Self {
+        self.pipeline
+            .draw(self.encoder, vertices, distances, Some(colors), self.cap);
+        self
+    }
+
+    /// Needed for `ColorSource::Values`.
+    pub fn set_colormap(&mut self, colormap: &'s ColormapBindGroup) -> &mut Self {
+        self.encoder.set_bind_group(1, &colormap.bind_group, &[]);
+        self
+    }
+
+    /// Draws ring buffer contents from oldest to newest, with two draw calls if contents wrap
+    /// around.
+    pub fn draw_ring(&mut self, ring: &'s RingBuffer<Point2d>) -> &mut Self {
+        let (first, second) = if P::LINE_MULTIPLIER == 1 {
+            ring.strip_slices()
         } else {
-            count /= P::LINE_MULTIPLIER;
+            ring.slices()
+        };
+        if first.len() >= 2 {
+            self.draw(first);
+        }
+        if second.len() >= 2 {
+            self.draw(second);
         }
-        // Since instance wertex buffers are sliced we start from 0
-        self.encoder.draw(0..6 as _, 0..count);
         self
     }
 }
@@ -198,66 +797,207 @@ const SEGMENT: [[f32; 2]; 6] = [
     [0., 0.5],
 ];
 
+/// Number of triangles in a fan for joins and caps, enough for round ones to look smooth.
+const FAN_TRIANGLES: u32 = 16;
+const FAN_VERTICES: u32 = FAN_TRIANGLES * 3;
+
+const POINT_SIZE: BufferAddress = mem::size_of::<Point2d>() as BufferAddress;
+const DISTANCE_SIZE: BufferAddress = mem::size_of::<f32>() as BufferAddress;
+
+/// Triangles around a center, x is position along the rim and y is 0 for center, see `line.wgsl`.
+fn fan() -> Vec<[f32; 2]> {
+    (0..FAN_TRIANGLES)
+        .flat_map(|i| {
+            let t0 = i as f32 / FAN_TRIANGLES as f32;
+            let t1 = (i + 1) as f32 / FAN_TRIANGLES as f32;
+            vec![[t0, 0.], [t0, 1.], [t1, 1.]]
+        })
+        .collect()
+}
+
+fn create_vbo(device: &Device, label: &str, vertices: &[[f32; 2]]) -> wgpu::Buffer {
+    device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+        label: Some(label),
+        contents: bytemuck::cast_slice(vertices),
+        usage: wgpu::BufferUsages::VERTEX,
+    })
+}
+
+/// Instance attribute with the stride of its buffer, see `separate` and `shared`.
+type InstanceAttribute = (BufferAddress, wgpu::VertexAttribute);
+
+/// Instance attributes that are read from one buffer slot, with the stride of the buffer.
+type InstanceBuffer = (BufferAddress, Vec<wgpu::VertexAttribute>);
+
+/// Every attribute in its own buffer slot so that the same buffer can be bound at different
+/// offsets eg. for consecutive points.
+fn separate(attributes: &[InstanceAttribute]) -> Vec<InstanceBuffer> {
+    attributes
+        .iter()
+        .map(|&(stride, attribute)| (stride, vec![attribute]))
+        .collect()
+}
+
+/// All attributes in one buffer slot, they must have the same stride. Saves slots when
+/// attributes are always read at fixed offsets from each other.
+fn shared(attributes: &[InstanceAttribute]) -> Vec<InstanceBuffer> {
+    match attributes.first() {
+        Some(&(stride, _)) => {
+            debug_assert!(attributes.iter().all(|x| x.0 == stride));
+            vec![(stride, attributes.iter().map(|x| x.1).collect())]
+        }
+        None => Vec::new(),
+    }
+}
+
+fn point_attribute(
+    location: ShaderLocation,
+    offset: BufferAddress,
+    stride_multiplier: BufferAddress,
+) -> InstanceAttribute {
+    (
+        POINT_SIZE * stride_multiplier,
+        wgpu::VertexAttribute {
+            offset,
+            shader_location: location,
+            format: wgpu::VertexFormat::Float32x2,
+        },
+    )
+}
+
+/// `offset` is in number of distances.
+fn distance_attribute(
+    location: ShaderLocation,
+    offset: BufferAddress,
+    stride_multiplier: BufferAddress,
+) -> InstanceAttribute {
+    (
+        DISTANCE_SIZE * stride_multiplier,
+        wgpu::VertexAttribute {
+            offset: offset * DISTANCE_SIZE,
+            shader_location: location,
+            format: wgpu::VertexFormat::Float32,
+        },
+    )
+}
+
+/// Attributes of a color stream, `locations` are pairs of shader location and offset in number
+/// of vertices. Empty for `ColorSource::Uniform`.
+fn color_attributes(
+    color_source: ColorSource,
+    locations: &[(ShaderLocation, BufferAddress)],
+    stride_multiplier: BufferAddress,
+) -> Vec<InstanceAttribute> {
+    let (size, format) = match color_source {
+        ColorSource::Uniform => return Vec::new(),
+        ColorSource::Colors => (
+            mem::size_of::<RGBA<f32>>() as BufferAddress,
+            wgpu::VertexFormat::Float32x4,
+        ),
+        ColorSource::Values => (
+            mem::size_of::<f32>() as BufferAddress,
+            wgpu::VertexFormat::Float32,
+        ),
+    };
+    locations
+        .iter()
+        .map(|&(location, offset)| {
+            (
+                size * stride_multiplier,
+                wgpu::VertexAttribute {
+                    offset: offset * size,
+                    shader_location: location,
+                    format,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Selects entry points of `line.wgsl`.
+struct PipelineKind {
+    /// "" for segments, "_strip" for segments of strips, "_join" or "_cap"
+    kind: &'static str,
+    color_source: ColorSource,
+    dashed: bool,
+}
+
+impl PipelineKind {
+    fn vertex_entry_point(&self) -> String {
+        let color = match self.color_source {
+            ColorSource::Uniform => "",
+            ColorSource::Colors => "_colored",
+            ColorSource::Values => "_mapped",
+        };
+        let dashed = if self.dashed { "_dashed" } else { "" };
+        format!("main{}{}{}", self.kind, color, dashed)
+    }
+
+    fn fragment_entry_point(&self) -> &'static str {
+        match (self.color_source, self.dashed) {
+            (ColorSource::Uniform, false) => "main",
+            (ColorSource::Uniform, true) => "main_dashed",
+            (_, false) => "main_colored",
+            (_, true) => "main_colored_dashed",
+        }
+    }
+}
+
+/// Vertex buffer 0 is the model (segment or fan) and every instance buffer is in the next one,
+/// slots follow the order of `instances` and not shader locations.
 fn create_pipeline(
     device: &Device,
     format: TextureFormat,
+    sample_count: u32,
+    blend: BlendMode,
     line_storage: &LineShader,
-    stride_multiplier: BufferAddress,
-) -> (wgpu::Buffer, RenderPipeline) {
-    let segment_vbo = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-        label: Some("line segment vbo"),
-        contents: bytemuck::cast_slice(&SEGMENT),
-        usage: wgpu::BufferUsages::VERTEX,
-    });
+    kind: PipelineKind,
+    instances: &[InstanceBuffer],
+) -> RenderPipeline {
+    let uniform_layout = LineBindGroup::layout(device);
+    let colormap_layout = ColormapBindGroup::layout(device);
+    let bind_group_layouts: &[&BindGroupLayout] = match kind.color_source {
+        ColorSource::Values => &[&uniform_layout, &colormap_layout],
+        _ => &[&uniform_layout],
+    };
     let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("line render pipeline layout"),
-        bind_group_layouts: &[&LineBindGroup::layout(device)],
+        bind_group_layouts,
         push_constant_ranges: &[],
     });
-    let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    let model_attribute = wgpu::VertexAttribute {
+        offset: 0,
+        shader_location: 0,
+        format: wgpu::VertexFormat::Float32x2,
+    };
+    let mut buffers = vec![wgpu::VertexBufferLayout {
+        array_stride: mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes: std::slice::from_ref(&model_attribute),
+    }];
+    buffers.extend(
+        instances
+            .iter()
+            .map(|(stride, attributes)| wgpu::VertexBufferLayout {
+                array_stride: *stride,
+                step_mode: wgpu::VertexStepMode::Instance,
+                attributes,
+            }),
+    );
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
         label: Some("line render pipeline"),
         layout: Some(&render_pipeline_layout),
         vertex: wgpu::VertexState {
             module: &line_storage.shader,
-            entry_point: "main",
-            buffers: &[
-                wgpu::VertexBufferLayout {
-                    array_stride: std::mem::size_of::<Point2d>() as wgpu::BufferAddress,
-                    step_mode: wgpu::VertexStepMode::Vertex,
-                    attributes: &[wgpu::VertexAttribute {
-                        offset: 0,
-                        shader_location: 0,
-                        format: wgpu::VertexFormat::Float32x2,
-                    }],
-                },
-                wgpu::VertexBufferLayout {
-                    array_stride: mem::size_of::<Point2d>() as wgpu::BufferAddress
-                        * stride_multiplier,
-                    step_mode: wgpu::VertexStepMode::Instance,
-                    attributes: &[wgpu::VertexAttribute {
-                        offset: 0,
-                        shader_location: 1,
-                        format: wgpu::VertexFormat::Float32x2,
-                    }],
-                },
-                wgpu::VertexBufferLayout {
-                    array_stride: mem::size_of::<Point2d>() as wgpu::BufferAddress
-                        * stride_multiplier,
-                    step_mode: wgpu::VertexStepMode::Instance,
-                    attributes: &[wgpu::VertexAttribute {
-                        offset: std::mem::size_of::<Point2d>() as wgpu::BufferAddress,
-                        shader_location: 2,
-                        format: wgpu::VertexFormat::Float32x2,
-                    }],
-                },
-            ],
+            entry_point: &kind.vertex_entry_point(),
+            buffers: &buffers,
         },
         fragment: Some(wgpu::FragmentState {
             module: &line_storage.shader,
-            entry_point: "main",
+            entry_point: kind.fragment_entry_point(),
             targets: &[wgpu::ColorTargetState {
                 format,
-                blend: Some(wgpu::BlendState::REPLACE),
+                blend: Some(blend.blend_state()),
                 write_mask: wgpu::ColorWrites::ALL,
             }],
         }),
@@ -271,13 +1011,8 @@ fn create_pipeline(
             conservative: false,
         },
         depth_stencil: None,
-        multisample: wgpu::MultisampleState {
-            count: 1,
-            mask: !0,
-            alpha_to_coverage_enabled: false,
-        },
-    });
-    (segment_vbo, render_pipeline)
+        multisample: multisample_state(sample_count),
+    })
 }
 
 pub trait LineRenderer: Sealed {
@@ -285,7 +1020,20 @@ pub trait LineRenderer: Sealed {
 }
 
 mod private {
-    pub trait Sealed {}
-    impl Sealed for super::LinePipeline {}
-    impl Sealed for super::LineStripPipeline {}
+    use super::{ColorStream, LineCap};
+    use crate::buffer::BufferSlice;
+    use crate::Point2d;
+    use wgpu::util::RenderEncoder;
+
+    pub trait Sealed {
+        /// Draws segments, joins and caps, dashed if there are distances.
+        fn draw<'s, E: RenderEncoder<'s>>(
+            &'s self,
+            encoder: &mut E,
+            vertices: BufferSlice<'s, Point2d>,
+            distances: Option<BufferSlice<'s, f32>>,
+            colors: Option<ColorStream<'s>>,
+            cap: LineCap,
+        );
+    }
 }
@@ -5,11 +5,35 @@ struct Uniform {
     scale: vec2<f32>;
     translate: vec2<f32>;
     line_scale: vec2<f32>;
+    join: u32;
+    cap: u32;
+    miter_limit: f32;
+    dash_count: u32;
+    dash_offset: f32;
+    dash_length: f32;
+    dash: array<vec4<f32>, 2>;
+    value_min: f32;
+    value_max: f32;
+    log_base: vec2<f32>;
 };
 
 [[group(0), binding(0)]]
 var<uniform> uni: Uniform;
 
+// only bound for pipelines that map values to colors
+[[group(1), binding(0)]]
+var colormap: texture_1d<f32>;
+
+let JOIN_MITER: u32 = 0u;
+let JOIN_ROUND: u32 = 1u;
+let JOIN_BEVEL: u32 = 2u;
+
+let CAP_BUTT: u32 = 0u;
+let CAP_SQUARE: u32 = 1u;
+let CAP_ROUND: u32 = 2u;
+
+let PI: f32 = 3.14159265;
+
 struct VertexInput {
     [[location(0)]] pos: vec2<f32>;
 };
@@ -23,28 +47,523 @@ struct VertexOutput {
     [[builtin(position)]] clip_position: vec4<f32>;
 };
 
+struct DashedVertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    // distance in pixels from the start of the line
+    [[location(0)]] distance: f32;
+};
+
+// distance is 0 for solid lines
+struct ColoredVertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] distance: f32;
+    [[location(1)]] color: vec4<f32>;
+};
+
+// Logarithm with base `log_base` on axes where it isn't 0.
+fn to_axis(p: vec2<f32>) -> vec2<f32> {
+    var out: vec2<f32> = p;
+    if (uni.log_base.x != 0.0) {
+        out.x = log(p.x) / log(uni.log_base.x);
+    }
+    if (uni.log_base.y != 0.0) {
+        out.y = log(p.y) / log(uni.log_base.y);
+    }
+    return out;
+}
+
+// Line space is clip space divided by line width, line is 1 unit wide and pixels are square in it
+// so that joins and caps keep their shape regardless of aspect ratio and zoom.
+fn to_line_space(p: vec2<f32>) -> vec2<f32> {
+    return (to_axis(p) * uni.scale + uni.translate) / uni.line_scale;
+}
+
+fn to_clip(p: vec2<f32>) -> vec4<f32> {
+    return vec4<f32>(p * uni.line_scale, 1.0, 1.0);
+}
+
+fn normal(dir: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(-dir.y, dir.x);
+}
+
+fn angle(v: vec2<f32>) -> f32 {
+    return atan2(v.y, v.x);
+}
+
+fn on_circle(center: vec2<f32>, a: f32) -> vec2<f32> {
+    return center + vec2<f32>(cos(a), sin(a)) * 0.5;
+}
+
+// Nearest color of the colormap, values outside of the range are clamped.
+fn map_value(value: f32) -> vec4<f32> {
+    let t = clamp((value - uni.value_min) / (uni.value_max - uni.value_min), 0.0, 1.0);
+    let last = textureDimensions(colormap) - 1;
+    return textureLoad(colormap, i32(round(t * f32(last))), 0);
+}
+
+fn colored(pos: vec2<f32>, distance: f32, color: vec4<f32>) -> ColoredVertexOutput {
+    var out: ColoredVertexOutput;
+    out.clip_position = to_clip(pos);
+    out.distance = distance;
+    out.color = color;
+    return out;
+}
+
+fn segment_position(model: VertexInput, instance: InstanceInput) -> vec2<f32> {
+    // see: https://wwwtyro.net/2019/11/18/instanced-lines.html
+    let first = to_line_space(instance.first);
+    let second = to_line_space(instance.second);
+    // length between 2 points
+    let xBasis = second - first;
+    // normal is a vector that is perpendicular over another vector
+    let yBasis = normalize(normal(xBasis));
+    // How to render a line with borders: render thicker line, then render thinner line with the same data
+    return first + xBasis * model.pos.x + yBasis * model.pos.y;
+}
+
 [[stage(vertex)]]
 fn main(
-    [[builtin(vertex_index)]] vid: u32,
     model: VertexInput,
     instance: InstanceInput,
 ) -> VertexOutput {
     var out: VertexOutput;
-    // see: https://wwwtyro.net/2019/11/18/instanced-lines.html
-    // length between 2 points
-    let xBasis = instance.second - instance.first;
-    // normal is a vector that is perpendicular over another vector
-    var yBasis: vec2<f32> = normalize(vec2<f32>(-xBasis.y, xBasis.x)); // direction of a normal
-    // TODO: prebake width into quad by multiplying all y values with width to avoid multiplying with it in shader
-    // How to render a line with borders: render thicker line, then render thinner line with the same data
-    // How to render a line with custom geometry at joints: render line then using different shader we draw with custom instance over same point data using only one vao for a point to not draw 2 times at the same spot
-    let pos = instance.first + xBasis * model.pos.x + yBasis * uni.line_scale * model.pos.y;
-    out.clip_position = vec4<f32>(pos * uni.scale + uni.translate, 1.0, 1.0);
+    out.clip_position = to_clip(segment_position(model, instance));
     return out;
 }
 
+[[stage(vertex)]]
+fn main_dashed(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(3)]] first_distance: f32,
+    [[location(4)]] second_distance: f32,
+) -> DashedVertexOutput {
+    var out: DashedVertexOutput;
+    out.clip_position = to_clip(segment_position(model, instance));
+    out.distance = mix(first_distance, second_distance, model.pos.x);
+    return out;
+}
+
+[[stage(vertex)]]
+fn main_colored(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(5)]] first_color: vec4<f32>,
+    [[location(6)]] second_color: vec4<f32>,
+) -> ColoredVertexOutput {
+    let color = mix(first_color, second_color, model.pos.x);
+    return colored(segment_position(model, instance), 0.0, color);
+}
+
+[[stage(vertex)]]
+fn main_colored_dashed(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(3)]] first_distance: f32,
+    [[location(4)]] second_distance: f32,
+    [[location(5)]] first_color: vec4<f32>,
+    [[location(6)]] second_color: vec4<f32>,
+) -> ColoredVertexOutput {
+    let distance = mix(first_distance, second_distance, model.pos.x);
+    let color = mix(first_color, second_color, model.pos.x);
+    return colored(segment_position(model, instance), distance, color);
+}
+
+// Values are mapped at both ends and interpolated between them.
+[[stage(vertex)]]
+fn main_mapped(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(5)]] first_value: f32,
+    [[location(6)]] second_value: f32,
+) -> ColoredVertexOutput {
+    let color = mix(map_value(first_value), map_value(second_value), model.pos.x);
+    return colored(segment_position(model, instance), 0.0, color);
+}
+
+[[stage(vertex)]]
+fn main_mapped_dashed(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(3)]] first_distance: f32,
+    [[location(4)]] second_distance: f32,
+    [[location(5)]] first_value: f32,
+    [[location(6)]] second_value: f32,
+) -> ColoredVertexOutput {
+    let distance = mix(first_distance, second_distance, model.pos.x);
+    let color = mix(map_value(first_value), map_value(second_value), model.pos.x);
+    return colored(segment_position(model, instance), distance, color);
+}
+
+// Point where inner edges of segments a-b and b-c meet, in line space. Segments of strips end
+// there on the inner side of the turn and the join is a fan around it, so nothing is drawn twice.
+// `b` if there is no turn or the corner is farther along than either segment is long, segments
+// overlap on the inner side then.
+fn inner_corner(a: vec2<f32>, b: vec2<f32>, c: vec2<f32>) -> vec2<f32> {
+    let dir0 = normalize(b - a);
+    let dir1 = normalize(c - b);
+    let turn = dir0.x * dir1.y - dir0.y * dir1.x;
+    if (turn == 0.0) {
+        return b;
+    }
+    // inner side is in the direction of the turn
+    let inner0 = normal(dir0) * sign(turn);
+    let inner1 = normal(dir1) * sign(turn);
+    let miter = normalize(inner0 + inner1);
+    let corner = b + miter * (0.5 / dot(miter, inner0));
+    let along = abs(dot(corner - b, dir1));
+    if (along > length(b - a) || along > length(c - b)) {
+        return b;
+    }
+    return corner;
+}
+
+// Segment of a strip that ends at inner corners with its neighbors, see `inner_corner`.
+// `prev` equals `first` and `next` equals `second` at the ends of the strip.
+fn strip_segment_position(
+    model: VertexInput,
+    instance: InstanceInput,
+    prev: vec2<f32>,
+    next: vec2<f32>,
+) -> vec2<f32> {
+    let first = to_line_space(instance.first);
+    let second = to_line_space(instance.second);
+    let x_basis = second - first;
+    let y_basis = normalize(normal(x_basis));
+    var end: vec2<f32> = first;
+    var corner: vec2<f32> = first;
+    if (model.pos.x == 0.0) {
+        if (any(prev != instance.first)) {
+            corner = inner_corner(to_line_space(prev), first, second);
+        }
+    } else {
+        end = second;
+        corner = second;
+        if (any(next != instance.second)) {
+            corner = inner_corner(first, second, to_line_space(next));
+        }
+    }
+    // only the vertex on the inner side moves
+    if (dot(corner - end, y_basis) * model.pos.y > 0.0) {
+        return corner;
+    }
+    return first + x_basis * model.pos.x + y_basis * model.pos.y;
+}
+
+[[stage(vertex)]]
+fn main_strip(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(7)]] prev: vec2<f32>,
+    [[location(8)]] next: vec2<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = to_clip(strip_segment_position(model, instance, prev, next));
+    return out;
+}
+
+[[stage(vertex)]]
+fn main_strip_dashed(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(3)]] first_distance: f32,
+    [[location(4)]] second_distance: f32,
+    [[location(7)]] prev: vec2<f32>,
+    [[location(8)]] next: vec2<f32>,
+) -> DashedVertexOutput {
+    var out: DashedVertexOutput;
+    out.clip_position = to_clip(strip_segment_position(model, instance, prev, next));
+    out.distance = mix(first_distance, second_distance, model.pos.x);
+    return out;
+}
+
+[[stage(vertex)]]
+fn main_strip_colored(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(5)]] first_color: vec4<f32>,
+    [[location(6)]] second_color: vec4<f32>,
+    [[location(7)]] prev: vec2<f32>,
+    [[location(8)]] next: vec2<f32>,
+) -> ColoredVertexOutput {
+    let color = mix(first_color, second_color, model.pos.x);
+    return colored(strip_segment_position(model, instance, prev, next), 0.0, color);
+}
+
+[[stage(vertex)]]
+fn main_strip_colored_dashed(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(3)]] first_distance: f32,
+    [[location(4)]] second_distance: f32,
+    [[location(5)]] first_color: vec4<f32>,
+    [[location(6)]] second_color: vec4<f32>,
+    [[location(7)]] prev: vec2<f32>,
+    [[location(8)]] next: vec2<f32>,
+) -> ColoredVertexOutput {
+    let distance = mix(first_distance, second_distance, model.pos.x);
+    let color = mix(first_color, second_color, model.pos.x);
+    return colored(strip_segment_position(model, instance, prev, next), distance, color);
+}
+
+[[stage(vertex)]]
+fn main_strip_mapped(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(5)]] first_value: f32,
+    [[location(6)]] second_value: f32,
+    [[location(7)]] prev: vec2<f32>,
+    [[location(8)]] next: vec2<f32>,
+) -> ColoredVertexOutput {
+    let color = mix(map_value(first_value), map_value(second_value), model.pos.x);
+    return colored(strip_segment_position(model, instance, prev, next), 0.0, color);
+}
+
+[[stage(vertex)]]
+fn main_strip_mapped_dashed(
+    model: VertexInput,
+    instance: InstanceInput,
+    [[location(3)]] first_distance: f32,
+    [[location(4)]] second_distance: f32,
+    [[location(5)]] first_value: f32,
+    [[location(6)]] second_value: f32,
+    [[location(7)]] prev: vec2<f32>,
+    [[location(8)]] next: vec2<f32>,
+) -> ColoredVertexOutput {
+    let distance = mix(first_distance, second_distance, model.pos.x);
+    let color = mix(map_value(first_value), map_value(second_value), model.pos.x);
+    return colored(strip_segment_position(model, instance, prev, next), distance, color);
+}
+
+// Joins and caps are fans of triangles around a point, `model.pos.x` is position along the rim
+// from 0 to 1 and `model.pos.y` is 0 for the center and 1 for the rim.
+// Styles that need fewer triangles than the fan has collapse the rest.
+
+// Join fills the corner at `second` between the ends of strip segments, it's a fan around the
+// inner corner with the rim on the outer side.
+fn join_position(model: VertexInput, first: vec2<f32>, second: vec2<f32>, third: vec2<f32>) -> vec2<f32> {
+    let a = to_line_space(first);
+    let b = to_line_space(second);
+    let c = to_line_space(third);
+    if (model.pos.y == 0.0) {
+        return inner_corner(a, b, c);
+    }
+    let dir0 = normalize(b - a);
+    let dir1 = normalize(c - b);
+    // outer side is opposite to the direction of the turn
+    var side: f32 = 1.0;
+    if (dir0.x * dir1.y - dir0.y * dir1.x > 0.0) {
+        side = -1.0;
+    }
+    let outer0 = normal(dir0) * side;
+    let outer1 = normal(dir1) * side;
+    let p0 = b + outer0 * 0.5;
+    let p1 = b + outer1 * 0.5;
+    let t = model.pos.x;
+    if (uni.join == JOIN_ROUND) {
+        var delta: f32 = angle(outer1) - angle(outer0);
+        if (delta > PI) {
+            delta = delta - 2.0 * PI;
+        } elseif (delta < -PI) {
+            delta = delta + 2.0 * PI;
+        }
+        return on_circle(b, angle(outer0) + delta * t);
+    }
+    if (uni.join == JOIN_MITER) {
+        let miter = normalize(outer0 + outer1);
+        // ratio of miter length to line width
+        let ratio = 1.0 / dot(miter, outer0);
+        // falls back to bevel over the limit
+        if (ratio <= uni.miter_limit) {
+            let tip = b + miter * ratio * 0.5;
+            if (t <= 0.5) {
+                return mix(p0, tip, t * 2.0);
+            }
+            return mix(tip, p1, t * 2.0 - 1.0);
+        }
+    }
+    return mix(p0, p1, t);
+}
+
+[[stage(vertex)]]
+fn main_join(
+    model: VertexInput,
+    [[location(1)]] first: vec2<f32>,
+    [[location(2)]] second: vec2<f32>,
+    [[location(3)]] third: vec2<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = to_clip(join_position(model, first, second, third));
+    return out;
+}
+
+[[stage(vertex)]]
+fn main_join_dashed(
+    model: VertexInput,
+    [[location(1)]] first: vec2<f32>,
+    [[location(2)]] second: vec2<f32>,
+    [[location(3)]] third: vec2<f32>,
+    [[location(4)]] distance: f32,
+) -> DashedVertexOutput {
+    var out: DashedVertexOutput;
+    out.clip_position = to_clip(join_position(model, first, second, third));
+    out.distance = distance;
+    return out;
+}
+
+// Joins have the color of the corner point.
+[[stage(vertex)]]
+fn main_join_colored(
+    model: VertexInput,
+    [[location(1)]] first: vec2<f32>,
+    [[location(2)]] second: vec2<f32>,
+    [[location(3)]] third: vec2<f32>,
+    [[location(5)]] color: vec4<f32>,
+) -> ColoredVertexOutput {
+    return colored(join_position(model, first, second, third), 0.0, color);
+}
+
+[[stage(vertex)]]
+fn main_join_colored_dashed(
+    model: VertexInput,
+    [[location(1)]] first: vec2<f32>,
+    [[location(2)]] second: vec2<f32>,
+    [[location(3)]] third: vec2<f32>,
+    [[location(4)]] distance: f32,
+    [[location(5)]] color: vec4<f32>,
+) -> ColoredVertexOutput {
+    return colored(join_position(model, first, second, third), distance, color);
+}
+
+[[stage(vertex)]]
+fn main_join_mapped(
+    model: VertexInput,
+    [[location(1)]] first: vec2<f32>,
+    [[location(2)]] second: vec2<f32>,
+    [[location(3)]] third: vec2<f32>,
+    [[location(5)]] value: f32,
+) -> ColoredVertexOutput {
+    return colored(join_position(model, first, second, third), 0.0, map_value(value));
+}
+
+[[stage(vertex)]]
+fn main_join_mapped_dashed(
+    model: VertexInput,
+    [[location(1)]] first: vec2<f32>,
+    [[location(2)]] second: vec2<f32>,
+    [[location(3)]] third: vec2<f32>,
+    [[location(4)]] distance: f32,
+    [[location(5)]] value: f32,
+) -> ColoredVertexOutput {
+    return colored(join_position(model, first, second, third), distance, map_value(value));
+}
+
+// Cap at `end` that points away from `toward`.
+fn cap_position(model: VertexInput, end: vec2<f32>, toward: vec2<f32>) -> vec2<f32> {
+    let center = to_line_space(end);
+    if (model.pos.y == 0.0 || uni.cap == CAP_BUTT) {
+        return center;
+    }
+    let dir = normalize(center - to_line_space(toward));
+    let n = normal(dir);
+    let t = model.pos.x;
+    var pos: vec2<f32>;
+    if (uni.cap == CAP_ROUND) {
+        // half circle from the normal through `dir` to the opposite normal
+        pos = on_circle(center, angle(n) - PI * t);
+    } else {
+        // walk along 3 sides of a half square, length of the walk is 2
+        let l = t * 2.0;
+        if (l < 0.5) {
+            pos = center + n * 0.5 + dir * l;
+        } elseif (l < 1.5) {
+            pos = center + n * (1.0 - l) + dir * 0.5;
+        } else {
+            pos = center - n * 0.5 + dir * (2.0 - l);
+        }
+    }
+    return pos;
+}
+
+[[stage(vertex)]]
+fn main_cap(
+    model: VertexInput,
+    [[location(1)]] end: vec2<f32>,
+    [[location(2)]] toward: vec2<f32>,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = to_clip(cap_position(model, end, toward));
+    return out;
+}
+
+// Caps have the color of the end point.
+[[stage(vertex)]]
+fn main_cap_colored(
+    model: VertexInput,
+    [[location(1)]] end: vec2<f32>,
+    [[location(2)]] toward: vec2<f32>,
+    [[location(3)]] color: vec4<f32>,
+) -> ColoredVertexOutput {
+    return colored(cap_position(model, end, toward), 0.0, color);
+}
+
+[[stage(vertex)]]
+fn main_cap_mapped(
+    model: VertexInput,
+    [[location(1)]] end: vec2<f32>,
+    [[location(2)]] toward: vec2<f32>,
+    [[location(3)]] value: f32,
+) -> ColoredVertexOutput {
+    return colored(cap_position(model, end, toward), 0.0, map_value(value));
+}
+
 // Fragment shader
 [[stage(fragment)]]
 fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
     return uni.color;
-}
\ No newline at end of file
+}
+
+fn in_dash(distance: f32) -> bool {
+    if (uni.dash_count == 0u) {
+        return true;
+    }
+    var d: f32 = (distance + uni.dash_offset) % uni.dash_length;
+    if (d < 0.0) {
+        d = d + uni.dash_length;
+    }
+    var i: u32 = 0u;
+    loop {
+        if (i >= uni.dash_count) {
+            break;
+        }
+        let dash = uni.dash[i / 4u][i % 4u];
+        if (d < dash) {
+            // even entries are dashes and odd ones gaps
+            return i % 2u == 0u;
+        }
+        d = d - dash;
+        i = i + 1u;
+    }
+    return true;
+}
+
+[[stage(fragment)]]
+fn main_dashed(in: DashedVertexOutput) -> [[location(0)]] vec4<f32> {
+    if (!in_dash(in.distance)) {
+        discard;
+    }
+    return uni.color;
+}
+
+[[stage(fragment)]]
+fn main_colored(in: ColoredVertexOutput) -> [[location(0)]] vec4<f32> {
+    return in.color;
+}
+
+[[stage(fragment)]]
+fn main_colored_dashed(in: ColoredVertexOutput) -> [[location(0)]] vec4<f32> {
+    if (!in_dash(in.distance)) {
+        discard;
+    }
+    return in.color;
+}
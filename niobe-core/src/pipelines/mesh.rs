@@ -1,4 +1,5 @@
 use crate::buffer::{Buffer, BufferSlice};
+use crate::pipelines::{multisample_state, BlendMode};
 use crate::{IndexFormat, Mesh2d, Point2d};
 use bytemuck::{Pod, Zeroable};
 use nalgebra_glm::Vec2;
@@ -20,11 +21,39 @@ pub struct MeshUniform {
     pub scale: Vec2,
     pub translate: Vec2,
     pub mesh_scale: Vec2,
+    /// see `AxisScale::log_bases`
+    pub log_base: Vec2,
 }
 
 unsafe impl Pod for MeshUniform {}
 unsafe impl Zeroable for MeshUniform {}
 
+/// Instance of a mesh that is scaled in data units, used for shapes whose size depends on data
+/// eg. candle bodies and bars.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct RectInstance {
+    pub pos: Point2d,
+    pub size: Vec2,
+}
+
+unsafe impl Pod for RectInstance {}
+unsafe impl Zeroable for RectInstance {}
+
+/// Per instance data that a `MeshPipeline` is created for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum InstanceLayout {
+    /// `Point2d` that the mesh is translated to, see `MeshDrawer::draw`.
+    Position,
+    /// `RectInstance`, mesh vertices are multiplied by its size, see `MeshDrawer::draw_rects`.
+    Rect,
+    /// `Point2d` with optional per instance `RGBA<f32>` color and `f32` size streams,
+    /// see `MeshDrawer::draw_markers`.
+    /// If there is a size stream mesh is multiplied by it, usually `mesh_scale` is then set to
+    /// one pixel so that sizes are in pixels.
+    Marker { color: bool, size: bool },
+}
+
 pub struct MeshShader {
     shader: ShaderModule,
 }
@@ -84,46 +113,129 @@ pub struct MeshPipeline {
 }
 
 impl MeshPipeline {
-    pub fn new(device: &Device, config: &SurfaceConfiguration, mesh_shader: &MeshShader) -> Self {
+    pub fn new(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        sample_count: u32,
+        blend: BlendMode,
+        mesh_shader: &MeshShader,
+    ) -> Self {
+        Self::with_instance_layout(
+            device,
+            config,
+            sample_count,
+            blend,
+            mesh_shader,
+            InstanceLayout::Position,
+        )
+    }
+
+    pub fn with_instance_layout(
+        device: &Device,
+        config: &SurfaceConfiguration,
+        sample_count: u32,
+        blend: BlendMode,
+        mesh_shader: &MeshShader,
+        instance_layout: InstanceLayout,
+    ) -> Self {
         let render_pipeline_layout =
             device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("line render pipeline layout"),
                 bind_group_layouts: &[&MeshBindGroup::layout(device)],
                 push_constant_ranges: &[],
             });
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("mesh render pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &mesh_shader.shader,
-                entry_point: "main",
-                buffers: &[
-                    wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<Point2d>() as wgpu::BufferAddress,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: &[wgpu::VertexAttribute {
+        let (entry_point, instance_buffer) = match instance_layout {
+            InstanceLayout::Position | InstanceLayout::Marker { .. } => (
+                "main",
+                wgpu::VertexBufferLayout {
+                    array_stride: mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &[wgpu::VertexAttribute {
+                        offset: 0,
+                        shader_location: 1,
+                        format: wgpu::VertexFormat::Float32x2,
+                    }],
+                },
+            ),
+            InstanceLayout::Rect => (
+                "main_rect",
+                wgpu::VertexBufferLayout {
+                    array_stride: mem::size_of::<RectInstance>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &[
+                        wgpu::VertexAttribute {
                             offset: 0,
-                            shader_location: 0,
+                            shader_location: 1,
+                            format: wgpu::VertexFormat::Float32x2,
+                        },
+                        wgpu::VertexAttribute {
+                            offset: mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                            shader_location: 2,
                             format: wgpu::VertexFormat::Float32x2,
+                        },
+                    ],
+                },
+            ),
+        };
+        let mut buffers = vec![
+            wgpu::VertexBufferLayout {
+                array_stride: std::mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                step_mode: wgpu::VertexStepMode::Vertex,
+                attributes: &[wgpu::VertexAttribute {
+                    offset: 0,
+                    shader_location: 0,
+                    format: wgpu::VertexFormat::Float32x2,
+                }],
+            },
+            instance_buffer,
+        ];
+        let (entry_point, fragment_entry_point) = match instance_layout {
+            InstanceLayout::Marker { color, size } => {
+                if color {
+                    buffers.push(wgpu::VertexBufferLayout {
+                        array_stride: mem::size_of::<RGBA<f32>>() as wgpu::BufferAddress,
+                        step_mode: wgpu::VertexStepMode::Instance,
+                        attributes: &[wgpu::VertexAttribute {
+                            offset: 0,
+                            shader_location: 2,
+                            format: wgpu::VertexFormat::Float32x4,
                         }],
-                    },
-                    wgpu::VertexBufferLayout {
-                        array_stride: mem::size_of::<Point2d>() as wgpu::BufferAddress,
+                    });
+                }
+                if size {
+                    buffers.push(wgpu::VertexBufferLayout {
+                        array_stride: mem::size_of::<f32>() as wgpu::BufferAddress,
                         step_mode: wgpu::VertexStepMode::Instance,
                         attributes: &[wgpu::VertexAttribute {
                             offset: 0,
-                            shader_location: 1,
-                            format: wgpu::VertexFormat::Float32x2,
+                            shader_location: 3,
+                            format: wgpu::VertexFormat::Float32,
                         }],
-                    },
-                ],
+                    });
+                }
+                match (color, size) {
+                    (false, false) => ("main", "main"),
+                    (true, false) => ("main_marker_color", "main_colored"),
+                    (false, true) => ("main_marker_size", "main"),
+                    (true, true) => ("main_marker_color_size", "main_colored"),
+                }
+            }
+            _ => (entry_point, "main"),
+        };
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("mesh render pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &mesh_shader.shader,
+                entry_point,
+                buffers: &buffers,
             },
             fragment: Some(wgpu::FragmentState {
                 module: &mesh_shader.shader,
-                entry_point: "main",
+                entry_point: fragment_entry_point,
                 targets: &[wgpu::ColorTargetState {
                     format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
+                    blend: Some(blend.blend_state()),
                     write_mask: wgpu::ColorWrites::ALL,
                 }],
             }),
@@ -137,11 +249,7 @@ impl MeshPipeline {
                 conservative: false,
             },
             depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
+            multisample: multisample_state(sample_count),
         });
         Self {
             pipeline: render_pipeline,
@@ -199,4 +307,36 @@ impl<'s, 'e, E: RenderEncoder<'s>> MeshDrawer<'e, E> {
             .draw_indexed(0..self.indices_len, 0, 0..instances.len());
         self
     }
+
+    /// Draws with a pipeline created for `InstanceLayout::Marker`, optional streams must match
+    /// the layout.
+    pub fn draw_markers(
+        &mut self,
+        positions: BufferSlice<'s, Point2d>,
+        colors: Option<BufferSlice<'s, RGBA<f32>>>,
+        sizes: Option<BufferSlice<'s, f32>>,
+    ) -> &mut Self {
+        self.encoder.set_vertex_buffer(1, positions.to_raw_slice());
+        let mut slot = 2;
+        if let Some(colors) = colors {
+            debug_assert_eq!(colors.len(), positions.len());
+            self.encoder.set_vertex_buffer(slot, colors.to_raw_slice());
+            slot += 1;
+        }
+        if let Some(sizes) = sizes {
+            debug_assert_eq!(sizes.len(), positions.len());
+            self.encoder.set_vertex_buffer(slot, sizes.to_raw_slice());
+        }
+        self.encoder
+            .draw_indexed(0..self.indices_len, 0, 0..positions.len());
+        self
+    }
+
+    /// Draws with a pipeline created for `InstanceLayout::Rect`.
+    pub fn draw_rects(&mut self, instances: BufferSlice<'s, RectInstance>) -> &mut Self {
+        self.encoder.set_vertex_buffer(1, instances.to_raw_slice());
+        self.encoder
+            .draw_indexed(0..self.indices_len, 0, 0..instances.len());
+        self
+    }
 }
@@ -5,11 +5,40 @@ struct Uniform {
     scale: vec2<f32>;
     translate: vec2<f32>;
     mesh_scale: vec2<f32>;
+    log_base: vec2<f32>;
 };
 
 [[group(0), binding(0)]]
 var<uniform> uni: Uniform;
 
+// Logarithm with base `log_base` on axes where it isn't 0.
+fn to_axis(p: vec2<f32>) -> vec2<f32> {
+    var out: vec2<f32> = p;
+    if (uni.log_base.x != 0.0) {
+        out.x = log(p.x) / log(uni.log_base.x);
+    }
+    if (uni.log_base.y != 0.0) {
+        out.y = log(p.y) / log(uni.log_base.y);
+    }
+    return out;
+}
+
+// Raises values below the bottom or left edge of the view on log axes to that edge, so that a
+// baseline at 0 whose logarithm is -inf still spans the visible part.
+fn clamp_to_view(p: vec2<f32>) -> vec2<f32> {
+    let first = (vec2<f32>(-1.0, -1.0) - uni.translate) / uni.scale;
+    let second = (vec2<f32>(1.0, 1.0) - uni.translate) / uni.scale;
+    let lowest = min(first, second);
+    var out: vec2<f32> = p;
+    if (uni.log_base.x != 0.0) {
+        out.x = max(p.x, lowest.x);
+    }
+    if (uni.log_base.y != 0.0) {
+        out.y = max(p.y, lowest.y);
+    }
+    return out;
+}
+
 struct VertexInput {
     [[location(0)]] pos: vec2<f32>;
 };
@@ -25,11 +54,73 @@ struct VertexOutput {
 [[stage(vertex)]]
 fn main( model: VertexInput, instance: InstanceInput) -> VertexOutput {
     var out: VertexOutput;
-    out.clip_position = vec4<f32>(model.pos * uni.mesh_scale + instance.pos * uni.scale + uni.translate, 1.0, 1.0);
+    out.clip_position = vec4<f32>(model.pos * uni.mesh_scale + to_axis(instance.pos) * uni.scale + uni.translate, 1.0, 1.0);
+    return out;
+}
+
+struct RectInstanceInput {
+    [[location(1)]] pos: vec2<f32>;
+    [[location(2)]] size: vec2<f32>;
+};
+
+// mesh is scaled by instance size in data units, mesh_scale is added on top of it
+// rects are clamped to the view on log axes, eg. bars from a baseline at 0
+[[stage(vertex)]]
+fn main_rect(model: VertexInput, instance: RectInstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+    let pos = clamp_to_view(to_axis(instance.pos + model.pos * instance.size));
+    out.clip_position = vec4<f32>(model.pos * uni.mesh_scale + pos * uni.scale + uni.translate, 1.0, 1.0);
+    return out;
+}
+
+struct ColoredVertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn main_marker_color(
+    model: VertexInput,
+    [[location(1)]] pos: vec2<f32>,
+    [[location(2)]] color: vec4<f32>,
+) -> ColoredVertexOutput {
+    var out: ColoredVertexOutput;
+    out.clip_position = vec4<f32>(model.pos * uni.mesh_scale + to_axis(pos) * uni.scale + uni.translate, 1.0, 1.0);
+    out.color = color;
+    return out;
+}
+
+// mesh_scale is the size of one pixel, size is in pixels
+[[stage(vertex)]]
+fn main_marker_size(
+    model: VertexInput,
+    [[location(1)]] pos: vec2<f32>,
+    [[location(3)]] size: f32,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(model.pos * size * uni.mesh_scale + to_axis(pos) * uni.scale + uni.translate, 1.0, 1.0);
+    return out;
+}
+
+[[stage(vertex)]]
+fn main_marker_color_size(
+    model: VertexInput,
+    [[location(1)]] pos: vec2<f32>,
+    [[location(2)]] color: vec4<f32>,
+    [[location(3)]] size: f32,
+) -> ColoredVertexOutput {
+    var out: ColoredVertexOutput;
+    out.clip_position = vec4<f32>(model.pos * size * uni.mesh_scale + to_axis(pos) * uni.scale + uni.translate, 1.0, 1.0);
+    out.color = color;
     return out;
 }
 
 [[stage(fragment)]]
 fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
     return uni.color;
+}
+
+[[stage(fragment)]]
+fn main_colored(in: ColoredVertexOutput) -> [[location(0)]] vec4<f32> {
+    return in.color;
 }
\ No newline at end of file
@@ -1,5 +1,5 @@
 use egui_wgpu_backend::ScreenDescriptor;
-use epaint::emath::{Pos2, Rect};
+use epaint::emath::{Pos2, Rect, Vec2};
 use epaint::text::{FontDefinitions, Fonts};
 use epaint::{
     ClippedMesh, Color32, Mesh, Shape, Stroke, TessellationOptions, Tessellator, TextStyle, Texture,
@@ -157,6 +157,14 @@ impl<'p> UiDrawer<'p> {
         self
     }
 
+    /// Size of `text` drawn with `UiDrawer::text_single_line`.
+    pub fn text_size(&self, style: TextStyle, text: impl Into<String>) -> Vec2 {
+        self.render_pass
+            .fonts
+            .layout_single_line(style, text.into())
+            .size
+    }
+
     pub fn text_single_line(
         &mut self,
         style: TextStyle,
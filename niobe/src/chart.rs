@@ -1,14 +1,12 @@
+use crate::components::crosshair::CrosshairConfig;
+use crate::components::grid::GridConfig;
 use crate::components::ChartState;
 use futures::executor::{LocalPool, LocalSpawner};
 use futures::StreamExt;
-use glyph_brush::ab_glyph::PxScale;
-use glyph_brush::Layout;
-use glyph_brush::{OwnedSection, OwnedText};
 use lexical::write_float_options::RoundMode;
 use nalgebra_glm::{TVec2, Vec2};
 use niobe_core::buffer::Buffer;
 use niobe_core::pipelines::line::{LineBindGroup, LineDrawer, LineStripPipeline, LineUniform};
-use niobe_core::pipelines::mesh::{MeshBindGroup, MeshDrawer, MeshUniform};
 use niobe_core::Point2d;
 use rgb::RGBA;
 use std::num::NonZeroUsize;
@@ -16,7 +14,7 @@ use tap::{Tap, TapOptional};
 use wgpu::util::{RenderEncoder, StagingBelt};
 use wgpu::{BufferUsages, Device, Queue, TextureFormat};
 use wgpu_glyph::ab_glyph::FontArc;
-use wgpu_glyph::{GlyphBrush, GlyphBrushBuilder, HorizontalAlign, Section, VerticalAlign};
+use wgpu_glyph::{GlyphBrush, GlyphBrushBuilder, Section};
 
 pub struct Chart {}
 
@@ -43,90 +41,6 @@ impl Chart {
     pub fn cursor_moved(&mut self) {}
 }
 
-struct Crosshair {
-    sections: Vec<OwnedSection>,
-}
-
-impl Crosshair {
-    fn new(config: &CrosshairConfig, scale_config: &Option<&ScaleConfig>) -> Self {
-        let mut sections = Vec::new();
-        if let Some(scale_config) = scale_config {
-            sections = vec![
-                OwnedSection {
-                    screen_position: (0.0, 0.0),
-                    bounds: (100.0, 100.0),
-                    layout: Layout::default_single_line().h_align(HorizontalAlign::Center),
-                    text: vec![OwnedText::new("".to_string())
-                        .with_scale(PxScale { x: 15.0, y: 15.0 })
-                        .with_color(colors::DARK_GRAY)],
-                },
-                OwnedSection {
-                    screen_position: (0.0, 0.0),
-                    bounds: (100.0, 100.0),
-                    layout: Layout::default_single_line().v_align(VerticalAlign::Center),
-                    text: vec![OwnedText::new("".to_string())
-                        .with_scale(PxScale { x: 15.0, y: 15.0 })
-                        .with_color(colors::DARK_GRAY)],
-                },
-            ];
-        }
-        Self {}
-    }
-}
-
-struct CrosshairScale {}
-
-impl CrosshairScale {
-    fn new(background_color: RGBA<f32>, device: &Device) -> Self {
-        Self {
-            mesh_ubo,
-            mesh_uniforms,
-            mesh_group,
-            mesh_instance_vbo,
-            mesh_instance_positions,
-        }
-    }
-
-    fn on_mouse_moved(
-        &mut self,
-        state: &ChartState,
-        write_options: &lexical::write_float_options::Options,
-    ) {
-    }
-
-    fn on_zoom(
-        &mut self,
-        state: &ChartState,
-        write_options: &lexical::write_float_options::Options,
-    ) {
-    }
-
-    fn draw_mesh<'s, 'e, E: RenderEncoder<'s>>(
-        &'s mut self,
-        drawer: &mut MeshDrawer<'e, E>,
-        pass_id: u8,
-    ) {
-        self.crosshair_scale
-            .tap_mut(|x| x.draw_mesh(drawer, pass_id));
-    }
-
-    fn draw_line_strip<'s, 'e, E: RenderEncoder<'s>>(
-        &'s mut self,
-        drawer: &mut LineDrawer<'s, 'e, E, LineStripPipeline>,
-        pass_id: u8,
-    ) {
-        drawer
-            .bind_group(&self.line_group, 0)
-            .draw(self.border_vbo.slice(..));
-    }
-
-    fn draw_text(&mut self, drawer: &mut GlyphBrush<()>, pass_id: u8) {
-        self.x_scale.tap_mut(|x| x.draw_text(drawer, pass_id));
-        self.crosshair_scale
-            .tap_mut(|x| x.draw_text(drawer, pass_id));
-    }
-}
-
 struct Scales {
     x_scale: Option<Scale>,
     y_scale: Option<Scale>,
@@ -140,7 +54,6 @@ struct Scales {
     border_width: u32,
     border_uniform: [LineUniform; 1],
     write_options: lexical::write_float_options::Options,
-    crosshair_scale: Option<CrosshairScale>,
     line_group: LineBindGroup,
 }
 
@@ -196,11 +109,6 @@ impl Scales {
                 .decimal_point(b'.')
                 .build()
                 .unwrap(),
-            crosshair_scale: if let Some(color) = config.crosshair_background_color {
-                Some(CrosshairScale::new(color, &device))
-            } else {
-                None
-            },
         }
     }
 
@@ -212,14 +120,7 @@ impl Scales {
             .write_sliced(&state.queue, .., &self.border_uniform);
     }
 
-    fn on_mouse_moved(&mut self, state: &ChartState) {
-        self.crosshair_scale
-            .tap_some(|x| x.on_mouse_moved(&state, &self.write_options));
-    }
-
     fn on_zoom(&mut self, state: &ChartState) {
-        self.crosshair_scale
-            .tap_some(|x| x.on_zoom(&state, &self.write_options));
         self.update_ticks(&state);
     }
 
@@ -227,15 +128,6 @@ impl Scales {
         self.update_ticks(&state);
     }
 
-    fn draw_mesh<'s, 'e, E: RenderEncoder<'s>>(
-        &'s mut self,
-        drawer: &mut MeshDrawer<'e, E>,
-        pass_id: u8,
-    ) {
-        self.crosshair_scale
-            .tap_mut(|x| x.draw_mesh(drawer, pass_id));
-    }
-
     fn draw_line_strip<'s, 'e, E: RenderEncoder<'s>>(
         &'s mut self,
         drawer: &mut LineDrawer<'s, 'e, E, LineStripPipeline>,
@@ -248,8 +140,6 @@ impl Scales {
 
     fn draw_text(&mut self, drawer: &mut GlyphBrush<()>, pass_id: u8) {
         self.x_scale.tap_mut(|x| x.draw_text(drawer, pass_id));
-        self.crosshair_scale
-            .tap_mut(|x| x.draw_text(drawer, pass_id));
     }
 
     fn update_ticks(&mut self, state: &ChartState) {
@@ -260,14 +150,4 @@ impl Scales {
     }
 }
 
-pub struct CrosshairConfig {
-    pub line_color: RGBA<f32>,
-    pub line_width: TVec2<u32>,
-}
-
-pub struct GridConfig {
-    pub line_color: RGBA<f32>,
-    pub line_width: TVec2<u32>,
-}
-
 const FORMAT: u128 = lexical::format::STANDARD;
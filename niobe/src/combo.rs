@@ -1,9 +1,11 @@
 use crate::components::view::{ChartView, DrawControlFlow, View};
-use crate::components::Component;
-use epaint::emath::{Pos2, Rect, Vec2};
+use crate::components::{node_rect, Component};
+use epaint::emath::{Pos2, Rect};
+use epaint::{Color32, Stroke};
+use nalgebra_glm as glm;
 use niobe_core::pipelines::line::{LineDrawer, LineStripPipeline};
 use niobe_core::pipelines::ui::UiDrawer;
-use niobe_core::pipelines::Drawer;
+use niobe_core::pipelines::{BlendMode, Drawer};
 use std::cell::RefCell;
 use std::rc::Rc;
 use stretch::node::Node;
@@ -11,29 +13,67 @@ use stretch::style::Style;
 use stretch::Stretch;
 use wgpu::util::RenderEncoder;
 use wgpu::RenderPass;
-use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::event::{ElementState, ModifiersState, MouseButton, MouseScrollDelta, WindowEvent};
 
 macro_rules! impl_draw {
     ($name:ident, $kind:ty) => {
-        pub fn $name<'s, 'e>(&mut self, drawer: &mut Drawer<'e, $kind>) -> DrawControlFlow {
+        /// Draws series of every view that use `blend`.
+        pub fn $name<'s, 'e>(
+            &mut self,
+            drawer: &mut Drawer<'e, $kind>,
+            blend: BlendMode,
+        ) -> DrawControlFlow {
             let mut control_flow = 0u8;
             for view in &mut self.views {
-                control_flow |= view.borrow_mut().$name(self, drawer) as u8;
+                control_flow |= view.borrow_mut().$name(self, drawer, blend) as u8;
             }
             unsafe { std::mem::transmute(control_flow) }
         }
     };
 }
 
+/// Selections smaller than this many pixels are treated as clicks.
+const MIN_BOX_ZOOM_SIZE: f32 = 4.;
+
+/// Axes that a box zoom changes, decided by where the drag started.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BoxZoomKind {
+    /// started inside a view
+    Both,
+    /// started above or below a view, eg. on the x scale
+    X,
+    /// started left or right of a view, eg. on the y scale
+    Y,
+}
+
+struct BoxZoom {
+    view: usize,
+    kind: BoxZoomKind,
+    /// pixels where the drag started
+    start: Pos2,
+}
+
 pub struct ChartCombo {
     flexbox: Stretch,
     pub node: Node,
     left_hold: bool,
+    modifiers: ModifiersState,
+    box_zoom: Option<BoxZoom>,
     mouse_pixel_pos: Pos2,
+    /// data to screen space transform shared by views and components
+    pub(crate) scale: glm::Vec2,
+    pub(crate) translate: glm::Vec2,
     views: Vec<View>,
     components: Vec<Rc<dyn Component>>,
 }
 
+/// Scale and translate that map data at screen space `selection` onto `view`.
+fn zoom_axis(scale: f32, translate: f32, view: (f32, f32), selection: (f32, f32)) -> (f32, f32) {
+    let new_scale = scale * (view.1 - view.0) / (selection.1 - selection.0);
+    let data_start = (selection.0 - translate) / scale;
+    (new_scale, view.0 - data_start * new_scale)
+}
+
 impl ChartCombo {
     pub fn new() -> Self {
         let mut flexbox = Stretch::new();
@@ -43,18 +83,19 @@ impl ChartCombo {
             flexbox,
             node,
             left_hold: false,
+            modifiers: ModifiersState::empty(),
+            box_zoom: None,
             mouse_pixel_pos: Default::default(),
+            scale: glm::vec2(1., 1.),
+            translate: glm::vec2(0., 0.),
             views: vec![],
             components: vec![],
         }
     }
 
+    /// Rect of `node` in pixels relative to the window, `node` may be nested in other nodes.
     pub fn component_rect(&self, node: Node) -> Rect {
-        let layout = self.flexbox.layout(node).unwrap();
-        Rect::from_min_size(
-            Pos2::new(layout.location.x, layout.location.y),
-            Vec2::new(layout.size.width, layout.size.height),
-        )
+        node_rect(&self.flexbox, self.node, node)
     }
 
     impl_draw!(
@@ -65,6 +106,114 @@ impl ChartCombo {
     pub fn draw_ui(&mut self, drawer: &mut UiDrawer) {
         self.views.iter().for_each(|x| x.draw_ui(delta));
         self.components.iter().for_each(|x| x.draw_ui(delta));
+        if let Some(selection) = self.box_zoom_selection() {
+            let fill = Color32::from_rgba_unmultiplied(120, 160, 255, 40);
+            let stroke = Stroke::new(1., Color32::from_rgb(120, 160, 255));
+            drawer.rect(selection, 0., fill, stroke);
+        }
+    }
+
+    /// Starts a box zoom in the view at `pos` or, if there is none, in the first view whose x or y
+    /// range contains it.
+    fn start_box_zoom(&mut self, pos: Pos2) {
+        let kinds = self.views.iter().enumerate().filter_map(|(i, view)| {
+            let rect = self.component_rect(view.node());
+            let in_x = pos.x >= rect.min.x && pos.x <= rect.max.x;
+            let in_y = pos.y >= rect.min.y && pos.y <= rect.max.y;
+            let kind = match (in_x, in_y) {
+                (true, true) => BoxZoomKind::Both,
+                (true, false) => BoxZoomKind::X,
+                (false, true) => BoxZoomKind::Y,
+                (false, false) => return None,
+            };
+            Some((i, kind))
+        });
+        let (mut both, mut band) = (None, None);
+        for (i, kind) in kinds {
+            if kind == BoxZoomKind::Both {
+                both = Some((i, kind));
+                break;
+            }
+            band = band.or(Some((i, kind)));
+        }
+        self.box_zoom = both.or(band).map(|(view, kind)| BoxZoom {
+            view,
+            kind,
+            start: pos,
+        });
+    }
+
+    /// Selected rect in pixels, bands span the whole view along the other axis.
+    fn box_zoom_selection(&self) -> Option<Rect> {
+        let box_zoom = self.box_zoom.as_ref()?;
+        let view = self.component_rect(self.views[box_zoom.view].node());
+        let mut selection = Rect::from_two_pos(box_zoom.start, self.mouse_pixel_pos);
+        selection = selection.intersect(view);
+        match box_zoom.kind {
+            BoxZoomKind::Both => {}
+            BoxZoomKind::X => {
+                selection.min.y = view.min.y;
+                selection.max.y = view.max.y;
+            }
+            BoxZoomKind::Y => {
+                selection.min.x = view.min.x;
+                selection.max.x = view.max.x;
+            }
+        }
+        Some(selection)
+    }
+
+    fn finish_box_zoom(&mut self) {
+        let selection = self.box_zoom_selection();
+        let box_zoom = match self.box_zoom.take() {
+            Some(x) => x,
+            None => return,
+        };
+        let selection = match selection {
+            Some(x) => x,
+            None => return,
+        };
+        let x = box_zoom.kind != BoxZoomKind::Y && selection.width() >= MIN_BOX_ZOOM_SIZE;
+        let y = box_zoom.kind != BoxZoomKind::X && selection.height() >= MIN_BOX_ZOOM_SIZE;
+        if x || y {
+            let view = self.component_rect(self.views[box_zoom.view].node());
+            self.zoom_to(view, selection, x, y);
+        }
+    }
+
+    /// Zooms so that data inside `selection` fills `view`, both are in pixels. An axis is left as
+    /// it is if `x` or `y` is false. Line widths are in pixels so they don't change.
+    fn zoom_to(&mut self, view: Rect, selection: Rect, x: bool, y: bool) {
+        let window = self.component_rect(self.node);
+        // pixels to screen space, y points up
+        let to_screen_x = |x: f32| (x - window.min.x) / window.width() * 2. - 1.;
+        let to_screen_y = |y: f32| 1. - (y - window.min.y) / window.height() * 2.;
+        if x {
+            let (scale, translate) = zoom_axis(
+                self.scale.x,
+                self.translate.x,
+                (to_screen_x(view.min.x), to_screen_x(view.max.x)),
+                (to_screen_x(selection.min.x), to_screen_x(selection.max.x)),
+            );
+            self.scale.x = scale;
+            self.translate.x = translate;
+        }
+        if y {
+            let (scale, translate) = zoom_axis(
+                self.scale.y,
+                self.translate.y,
+                (to_screen_y(view.max.y), to_screen_y(view.min.y)),
+                (to_screen_y(selection.max.y), to_screen_y(selection.min.y)),
+            );
+            self.scale.y = scale;
+            self.translate.y = translate;
+        }
+        let (scale, translate) = (self.scale, self.translate);
+        self.views
+            .iter_mut()
+            .for_each(|x| x.set_transform(scale, translate));
+        // scales, grid, crosshair and tooltip read the new transform
+        self.components.iter().for_each(|x| x.on_zoom(self));
     }
 
     pub fn zoom(&mut self, mut delta: f32) {
@@ -80,12 +229,45 @@ impl ChartCombo {
 
     pub fn input(&mut self, event: &WindowEvent) {
         match event {
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = *modifiers;
+            }
+            // box zoom with right drag or shift and left drag
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Right,
+                ..
+            } => {
+                self.start_box_zoom(self.mouse_pixel_pos);
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Pressed,
+                button: MouseButton::Left,
+                ..
+            } if self.modifiers.shift() => {
+                self.start_box_zoom(self.mouse_pixel_pos);
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Right,
+                ..
+            } => {
+                self.finish_box_zoom();
+            }
+            WindowEvent::MouseInput {
+                state: ElementState::Released,
+                button: MouseButton::Left,
+                ..
+            } if self.box_zoom.is_some() => {
+                self.finish_box_zoom();
+            }
             WindowEvent::MouseInput {
                 state: ElementState::Pressed,
                 button: MouseButton::Left,
                 ..
             } => {
-                self.left_hold = true;
+                let handled = self.components.iter().any(|x| x.on_click(self));
+                self.left_hold = !handled;
             }
             WindowEvent::MouseInput {
                 state: ElementState::Released,
@@ -113,7 +295,7 @@ impl ChartCombo {
                     .iter()
                     .for_each(|x| x.on_mouse_moved(self, delta));
                 self.components.iter().for_each(|x| x.on_mouse_moved(self));
-                if left_hold {
+                if left_hold && self.box_zoom.is_none() {
                     self.views.iter().for_each(|x| x.on_pan(self, delta));
                     self.components.iter().for_each(|x| x.on_pan(self, delta));
                 }
@@ -17,8 +17,12 @@ use wgpu::util::RenderEncoder;
 use wgpu::{Device, Queue, RenderPass};
 use wgpu_glyph::GlyphBrush;
 
+pub mod crosshair;
+pub mod grid;
+pub mod legend;
 pub mod scale;
 pub mod scale_highlight;
+pub mod tooltip;
 pub mod view;
 
 pub struct ChartState {
@@ -52,6 +56,11 @@ pub trait Component {
     fn on_mouse_moved(&mut self, state: &ChartCombo);
     fn on_zoom(&mut self, state: &ChartCombo);
     fn on_pan(&mut self, state: &ChartCombo);
+    /// Left click at the mouse position, returns true if it was handled so that it doesn't start
+    /// panning.
+    fn on_click(&mut self, state: &ChartState) -> bool {
+        false
+    }
     fn draw_ui(&mut self, combo: &ChartCombo, drawer: &mut UiPipeline);
     fn pipeline_kind(&self) -> PipelineKind;
     fn draw<'a>(&self, drawer: &mut RenderPass<'a>);
@@ -79,6 +88,25 @@ pub trait Component {
 //    }
 //}
 //
+/// Rect of `node` in pixels, stretch layouts are relative to the parent so locations of the
+/// ancestors between `root` and `node` are added up.
+pub(crate) fn node_rect(flexbox: &Stretch, root: Node, node: Node) -> Rect {
+    fn find(flexbox: &Stretch, current: Node, origin: Pos2, node: Node) -> Option<Rect> {
+        let layout = flexbox.layout(current).ok()?;
+        let min = origin + emath::vec2(layout.location.x, layout.location.y);
+        if current == node {
+            let size = emath::vec2(layout.size.width, layout.size.height);
+            return Some(Rect::from_min_size(min, size));
+        }
+        flexbox
+            .children(current)
+            .ok()?
+            .into_iter()
+            .find_map(|child| find(flexbox, child, min, node))
+    }
+    find(flexbox, root, Pos2::ZERO, node).expect("node is a descendant of root")
+}
+
 pub struct ComponentBase {
     pub node: Node,
     pub fill_color: Color32,
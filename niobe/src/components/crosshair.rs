@@ -0,0 +1,189 @@
+use crate::combo::ChartCombo;
+use crate::components::grid::{draw_line, GridLineStyle};
+use crate::components::scale::Scale;
+use crate::components::scale_highlight::ScaleHighlight;
+use crate::components::{ChartState, Component};
+use crate::series::nearest::{snap, SeriesPoints, SnapMode};
+use epaint::emath::{Pos2, Rect, Vec2 as EVec2};
+use epaint::Color32;
+use nalgebra_glm::Vec2;
+use niobe_core::pipelines::AxisScale;
+use niobe_core::{Point2d, UiPipeline};
+use std::cell::RefCell;
+use std::rc::Rc;
+use stretch::node::Node;
+
+#[derive(Clone, Debug)]
+pub struct CrosshairConfig {
+    /// vertical line
+    pub x_line: Option<GridLineStyle>,
+    /// horizontal line
+    pub y_line: Option<GridLineStyle>,
+    pub snap: SnapMode,
+}
+
+impl Default for CrosshairConfig {
+    fn default() -> Self {
+        let line = GridLineStyle::dashed(1., Color32::from_gray(150));
+        Self {
+            x_line: Some(line.clone()),
+            y_line: Some(line),
+            snap: SnapMode::None,
+        }
+    }
+}
+
+/// Lines through the cursor while it's inside a `View`, optionally snapped to points of a series.
+/// Moves `ScaleHighlight`s of kind `FollowCrosshair` so they show values at the crosshair.
+pub struct Crosshair {
+    view: Node,
+    config: CrosshairConfig,
+    x_scale: Option<Rc<RefCell<Scale>>>,
+    y_scale: Option<Rc<RefCell<Scale>>>,
+    highlights: Vec<Rc<RefCell<ScaleHighlight>>>,
+    series: Option<Rc<RefCell<dyn SeriesPoints>>>,
+    /// screen space position, none if the cursor is outside of the view
+    pos: Option<Vec2>,
+    /// pixels of `pos`
+    pixel_pos: Option<Pos2>,
+    /// index of the point that the crosshair snapped to
+    snapped: Option<usize>,
+}
+
+impl Crosshair {
+    pub fn new(
+        config: &CrosshairConfig,
+        view: Node,
+        x_scale: Option<Rc<RefCell<Scale>>>,
+        y_scale: Option<Rc<RefCell<Scale>>>,
+    ) -> Self {
+        Self {
+            view,
+            config: config.clone(),
+            x_scale,
+            y_scale,
+            highlights: Vec::new(),
+            series: None,
+            pos: None,
+            pixel_pos: None,
+            snapped: None,
+        }
+    }
+
+    pub fn add_highlight(&mut self, highlight: Rc<RefCell<ScaleHighlight>>) {
+        self.highlights.push(highlight);
+    }
+
+    /// Series that is snapped to unless snap mode is `SnapMode::None`.
+    pub fn set_snap_series(&mut self, series: Option<Rc<RefCell<dyn SeriesPoints>>>) {
+        self.series = series;
+        self.snapped = None;
+    }
+
+    pub fn set_snap_mode(&mut self, mode: SnapMode) {
+        self.config.snap = mode;
+    }
+
+    /// Data position of the crosshair, none if it's hidden.
+    pub fn data_pos(&self, state: &ChartState) -> Option<Point2d> {
+        let pos = self.pos?;
+        let (x, y) = self.axis_scales();
+        Some(Point2d::new(
+            x.inverse((pos.x - state.translate.x) / state.scale.x),
+            y.inverse((pos.y - state.translate.y) / state.scale.y),
+        ))
+    }
+
+    pub fn snapped_index(&self) -> Option<usize> {
+        self.snapped
+    }
+
+    fn axis_scales(&self) -> (AxisScale, AxisScale) {
+        let axis_scale = |x: &Option<Rc<RefCell<Scale>>>| {
+            x.as_ref()
+                .map_or(AxisScale::Linear, |x| x.borrow().axis_scale())
+        };
+        (axis_scale(&self.x_scale), axis_scale(&self.y_scale))
+    }
+
+    fn update(&mut self, state: &ChartState) {
+        let layout = state.flexbox.layout(self.view).unwrap();
+        let rect = Rect::from_min_size(
+            Pos2::new(layout.location.x, layout.location.y),
+            EVec2::new(layout.size.width, layout.size.height),
+        );
+        let (x_scale, y_scale) = self.axis_scales();
+        let to_screen = |point: Point2d| {
+            Vec2::new(
+                x_scale.forward(point.x) * state.scale.x + state.translate.x,
+                y_scale.forward(point.y) * state.scale.y + state.translate.y,
+            )
+        };
+        let to_pixels = |pos: Vec2| {
+            Vec2::new(
+                (pos.x + 1.) / state.pixel_scale.x,
+                (1. - pos.y) / state.pixel_scale.y,
+            )
+        };
+        let mouse = to_pixels(state.mouse_pos);
+        if !rect.contains(Pos2::new(mouse.x, mouse.y)) {
+            self.pos = None;
+            self.pixel_pos = None;
+            self.snapped = None;
+            return;
+        }
+        let mut pos = state.mouse_pos;
+        self.pos = Some(pos);
+        self.snapped = match (&self.series, self.data_pos(state)) {
+            (Some(series), Some(target)) => {
+                snap(&*series.borrow(), self.config.snap, target, |x| {
+                    to_pixels(to_screen(x))
+                })
+            }
+            _ => None,
+        };
+        if let (Some(i), Some(series)) = (self.snapped, &self.series) {
+            pos = to_screen(series.borrow().point(i));
+            self.pos = Some(pos);
+        }
+        let pixels = to_pixels(pos);
+        self.pixel_pos = Some(Pos2::new(pixels.x, pixels.y));
+        for highlight in &self.highlights {
+            highlight.borrow_mut().move_to(state, pos);
+        }
+    }
+}
+
+impl Component for Crosshair {
+    fn on_mouse_moved(&mut self, state: &ChartState) {
+        self.update(state);
+    }
+
+    fn on_zoom(&mut self, state: &ChartState) {
+        self.update(state);
+    }
+
+    fn on_pan(&mut self, state: &ChartState) {
+        self.update(state);
+    }
+
+    fn draw_ui(&mut self, combo: &ChartCombo, drawer: &mut UiPipeline) {
+        let pos = match self.pixel_pos {
+            Some(x) => x,
+            None => return,
+        };
+        let rect = combo.component_rect(self.view);
+        if let Some(style) = &self.config.x_line {
+            if pos.x >= rect.min.x && pos.x <= rect.max.x {
+                let start = Pos2::new(pos.x, rect.min.y);
+                draw_line(drawer, start, Pos2::new(pos.x, rect.max.y), style);
+            }
+        }
+        if let Some(style) = &self.config.y_line {
+            if pos.y >= rect.min.y && pos.y <= rect.max.y {
+                let start = Pos2::new(rect.min.x, pos.y);
+                draw_line(drawer, start, Pos2::new(rect.max.x, pos.y), style);
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use crate::combo::ChartCombo;
+use crate::components::scale::{Orientation, Scale};
+use crate::components::{ChartState, Component};
+use epaint::emath::{Pos2, Vec2};
+use epaint::{Color32, Stroke};
+use niobe_core::pipelines::line::LineDash;
+use niobe_core::UiPipeline;
+use std::cell::RefCell;
+use std::rc::Rc;
+use stretch::node::Node;
+
+/// Style of grid lines at major or minor ticks.
+#[derive(Clone, Debug)]
+pub struct GridLineStyle {
+    pub stroke: Stroke,
+    /// solid if none
+    pub dash: Option<LineDash>,
+}
+
+impl GridLineStyle {
+    pub fn solid(width: f32, color: Color32) -> Self {
+        Self {
+            stroke: Stroke::new(width, color),
+            dash: None,
+        }
+    }
+
+    pub fn dashed(width: f32, color: Color32) -> Self {
+        Self {
+            stroke: Stroke::new(width, color),
+            dash: Some(LineDash::dashed(width)),
+        }
+    }
+}
+
+/// Lines of one axis, vertical lines at ticks of a horizontal scale and the other way around.
+#[derive(Clone, Debug)]
+pub struct GridAxisStyle {
+    pub major: Option<GridLineStyle>,
+    pub minor: Option<GridLineStyle>,
+}
+
+impl Default for GridAxisStyle {
+    fn default() -> Self {
+        Self {
+            major: Some(GridLineStyle::solid(1., Color32::from_gray(60))),
+            minor: Some(GridLineStyle::dashed(1., Color32::from_gray(40))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GridConfig {
+    /// lines at ticks of the horizontal scale
+    pub x: GridAxisStyle,
+    /// lines at ticks of the vertical scale
+    pub y: GridAxisStyle,
+}
+
+/// Draws lines across a `View` at the ticks of its scales, ticks are read when drawing.
+pub struct Grid {
+    /// node of the view that lines are clipped to
+    view: Node,
+    x_scale: Option<Rc<RefCell<Scale>>>,
+    y_scale: Option<Rc<RefCell<Scale>>>,
+    config: GridConfig,
+}
+
+impl Grid {
+    pub fn new(
+        config: &GridConfig,
+        view: Node,
+        x_scale: Option<Rc<RefCell<Scale>>>,
+        y_scale: Option<Rc<RefCell<Scale>>>,
+    ) -> Self {
+        Self {
+            view,
+            x_scale,
+            y_scale,
+            config: config.clone(),
+        }
+    }
+}
+
+impl Component for Grid {
+    fn on_zoom(&mut self, _state: &ChartState) {}
+
+    fn on_pan(&mut self, _state: &ChartState) {}
+
+    fn draw_ui(&mut self, combo: &ChartCombo, drawer: &mut UiPipeline) {
+        let rect = combo.component_rect(self.view);
+        let window = combo.component_rect(combo.node).size();
+        let x_lines = lines(window, self.x_scale.as_ref());
+        let y_lines = lines(window, self.y_scale.as_ref());
+        // minor lines go first so that majors are drawn over them
+        for &major in &[false, true] {
+            let style = |x: &GridAxisStyle| {
+                if major {
+                    x.major.clone()
+                } else {
+                    x.minor.clone()
+                }
+            };
+            if let Some(style) = style(&self.config.x) {
+                for &(x, _) in x_lines.iter().filter(|(_, x)| *x == major) {
+                    if x >= rect.min.x && x <= rect.max.x {
+                        let start = Pos2::new(x, rect.min.y);
+                        let end = Pos2::new(x, rect.max.y);
+                        draw_line(drawer, start, end, &style);
+                    }
+                }
+            }
+            if let Some(style) = style(&self.config.y) {
+                for &(y, _) in y_lines.iter().filter(|(_, x)| *x == major) {
+                    if y >= rect.min.y && y <= rect.max.y {
+                        let start = Pos2::new(rect.min.x, y);
+                        let end = Pos2::new(rect.max.x, y);
+                        draw_line(drawer, start, end, &style);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Converts current ticks of `scale` into pixel positions along its axis and whether they are
+/// major, `window` is the size of the window in pixels.
+fn lines(window: Vec2, scale: Option<&Rc<RefCell<Scale>>>) -> Vec<(f32, bool)> {
+    let scale = match scale {
+        Some(x) => x.borrow(),
+        None => return Vec::new(),
+    };
+    let orientation = scale.orientation();
+    scale
+        .ticks()
+        .iter()
+        .map(|tick| {
+            let pos = match orientation {
+                Orientation::Horizontal => (tick.screen_pos + 1.) / 2. * window.x,
+                Orientation::Vertical => (1. - tick.screen_pos) / 2. * window.y,
+            };
+            (pos, tick.major)
+        })
+        .collect()
+}
+
+/// Draws an axis aligned line, dashes start at `start` so they don't move when panning along the
+/// line.
+pub(crate) fn draw_line(drawer: &mut UiPipeline, start: Pos2, end: Pos2, style: &GridLineStyle) {
+    let dash = match &style.dash {
+        Some(x) => x,
+        None => {
+            drawer.line(start, end, style.stroke);
+            return;
+        }
+    };
+    let length = (end - start).length();
+    if length <= 0. {
+        return;
+    }
+    let direction = (end - start) / length;
+    for dash in dash.dashes(length) {
+        let a = start + direction * dash.start;
+        let b = start + direction * dash.end;
+        drawer.line(a, b, style.stroke);
+    }
+}
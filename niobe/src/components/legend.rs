@@ -0,0 +1,246 @@
+use crate::combo::ChartCombo;
+use crate::components::crosshair::Crosshair;
+use crate::components::scale::Scale;
+use crate::components::{node_rect, ChartState, Component};
+use crate::series::nearest::{nearest_x, SeriesPoints};
+use epaint::emath::{Pos2, Rect, Vec2};
+use epaint::{Color32, Stroke, TextStyle};
+use niobe_core::UiPipeline;
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use stretch::geometry;
+use stretch::node::Node;
+use stretch::style::{Dimension, PositionType, Style};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Where a legend is placed, see `Legend::style`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LegendAnchor {
+    /// over a view at a corner, legend node must be a child of the view node
+    Inside { corner: Corner, margin: f32 },
+    /// flex item next to the view
+    Outside,
+}
+
+#[derive(Clone, Debug)]
+pub struct LegendConfig {
+    pub text_style: TextStyle,
+    pub text_color: Color32,
+    /// text color of hidden series
+    pub hidden_color: Color32,
+    pub fill_color: Color32,
+    pub row_height: f32,
+    pub swatch_size: f32,
+    pub padding: f32,
+}
+
+impl Default for LegendConfig {
+    fn default() -> Self {
+        Self {
+            text_style: TextStyle::Body,
+            text_color: Color32::from_gray(200),
+            hidden_color: Color32::from_gray(100),
+            fill_color: Color32::from_black_alpha(160),
+            row_height: 18.,
+            swatch_size: 10.,
+            padding: 6.,
+        }
+    }
+}
+
+struct LegendEntry {
+    name: String,
+    color: Color32,
+    series: Rc<RefCell<dyn SeriesPoints>>,
+    visible: Rc<Cell<bool>>,
+    /// value at the crosshair or the latest one
+    value: Option<String>,
+}
+
+/// Swatch, name and value of every series, clicking an entry toggles visibility of the series.
+/// Values are taken at the crosshair x, the latest value is shown when the crosshair is hidden.
+/// Reads the crosshair position so it must be added after the crosshair.
+pub struct Legend {
+    node: Node,
+    config: LegendConfig,
+    crosshair: Option<Rc<RefCell<Crosshair>>>,
+    /// formats values like labels of the scale
+    y_scale: Option<Rc<RefCell<Scale>>>,
+    entries: Vec<LegendEntry>,
+}
+
+impl Legend {
+    pub fn new(
+        config: &LegendConfig,
+        node: Node,
+        crosshair: Option<Rc<RefCell<Crosshair>>>,
+        y_scale: Option<Rc<RefCell<Scale>>>,
+    ) -> Self {
+        Self {
+            node,
+            config: config.clone(),
+            crosshair,
+            y_scale,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Style of the legend node with `size` in pixels.
+    pub fn style(anchor: LegendAnchor, size: geometry::Size<f32>) -> Style {
+        let size = geometry::Size {
+            width: Dimension::Points(size.width),
+            height: Dimension::Points(size.height),
+        };
+        match anchor {
+            LegendAnchor::Inside { corner, margin } => {
+                let mut position = geometry::Rect {
+                    start: Dimension::Undefined,
+                    end: Dimension::Undefined,
+                    top: Dimension::Undefined,
+                    bottom: Dimension::Undefined,
+                };
+                match corner {
+                    Corner::TopLeft | Corner::BottomLeft => {
+                        position.start = Dimension::Points(margin)
+                    }
+                    Corner::TopRight | Corner::BottomRight => {
+                        position.end = Dimension::Points(margin)
+                    }
+                }
+                match corner {
+                    Corner::TopLeft | Corner::TopRight => position.top = Dimension::Points(margin),
+                    Corner::BottomLeft | Corner::BottomRight => {
+                        position.bottom = Dimension::Points(margin)
+                    }
+                }
+                Style {
+                    position_type: PositionType::Absolute,
+                    position,
+                    size,
+                    ..Default::default()
+                }
+            }
+            LegendAnchor::Outside => Style {
+                size,
+                flex_shrink: 0.,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Adds an entry, clicks toggle `visible` which is the `visibility` flag of the series.
+    pub fn add_entry(
+        &mut self,
+        name: impl Into<String>,
+        color: Color32,
+        series: Rc<RefCell<dyn SeriesPoints>>,
+        visible: Rc<Cell<bool>>,
+    ) {
+        self.entries.push(LegendEntry {
+            name: name.into(),
+            color,
+            series,
+            visible,
+            value: None,
+        });
+    }
+
+    /// Inside legends are nested in the view so this is not just the layout of the node.
+    fn rect(&self, state: &ChartState) -> Rect {
+        node_rect(&state.flexbox, state.node, self.node)
+    }
+
+    fn update_values(&mut self, state: &ChartState) {
+        let x = self
+            .crosshair
+            .as_ref()
+            .and_then(|x| x.borrow().data_pos(state))
+            .map(|x| x.x);
+        let y_scale = self.y_scale.as_ref().map(|x| x.borrow());
+        for entry in &mut self.entries {
+            let series = entry.series.borrow();
+            let point = match x {
+                Some(x) => nearest_x(&*series, x).map(|i| series.point(i)),
+                None => series.last(),
+            };
+            entry.value = point.map(|point| match &y_scale {
+                Some(scale) => scale.convert_to_text(point.y),
+                None => point.y.to_string(),
+            });
+        }
+    }
+}
+
+impl Component for Legend {
+    fn on_mouse_moved(&mut self, state: &ChartState) {
+        self.update_values(state);
+    }
+
+    fn on_zoom(&mut self, state: &ChartState) {
+        self.update_values(state);
+    }
+
+    fn on_pan(&mut self, state: &ChartState) {
+        self.update_values(state);
+    }
+
+    fn on_click(&mut self, state: &ChartState) -> bool {
+        let rect = self.rect(state);
+        let mouse = Pos2::new(
+            (state.mouse_pos.x + 1.) / state.pixel_scale.x,
+            (1. - state.mouse_pos.y) / state.pixel_scale.y,
+        );
+        if !rect.contains(mouse) {
+            return false;
+        }
+        let row = (mouse.y - rect.min.y - self.config.padding) / self.config.row_height;
+        if row >= 0. {
+            if let Some(entry) = self.entries.get(row as usize) {
+                entry.visible.set(!entry.visible.get());
+            }
+        }
+        true
+    }
+
+    fn draw_ui(&mut self, combo: &ChartCombo, drawer: &mut UiPipeline) {
+        let rect = combo.component_rect(self.node);
+        let config = &self.config;
+        drawer.rect(rect, 2., config.fill_color, Stroke::default());
+        for (i, entry) in self.entries.iter().enumerate() {
+            let top = rect.min.y + config.padding + i as f32 * config.row_height;
+            let center = top + config.row_height / 2.;
+            let visible = entry.visible.get();
+            let swatch = Rect::from_center_size(
+                Pos2::new(
+                    rect.min.x + config.padding + config.swatch_size / 2.,
+                    center,
+                ),
+                Vec2::splat(config.swatch_size),
+            );
+            let (fill, stroke) = if visible {
+                (entry.color, Stroke::default())
+            } else {
+                (Color32::TRANSPARENT, Stroke::new(1., entry.color))
+            };
+            drawer.rect(swatch, 0., fill, stroke);
+            let text = match &entry.value {
+                Some(value) => format!("{} {}", entry.name, value),
+                None => entry.name.clone(),
+            };
+            let color = if visible {
+                config.text_color
+            } else {
+                config.hidden_color
+            };
+            let pos = Pos2::new(swatch.max.x + config.padding, top);
+            drawer.text_single_line(config.text_style, text, pos, color);
+        }
+    }
+}
@@ -4,8 +4,11 @@ use crate::{Layout, Layoutable};
 use glyph_brush::{OwnedSection, OwnedText};
 use nalgebra_glm::{TVec2, Vec2};
 use niobe_core::buffer::Buffer;
-use niobe_core::pipelines::line::{LineBindGroup, LineDrawer, LineStripPipeline, LineUniform};
+use niobe_core::pipelines::line::{
+    LineBindGroup, LineDrawer, LineStripPipeline, LineStyle, LineUniform,
+};
 use niobe_core::pipelines::mesh::MeshDrawer;
+use niobe_core::pipelines::AxisScale;
 use niobe_core::{Point2d, UiPipeline, ComponentColors};
 use rgb::RGBA;
 use std::num::NonZeroUsize;
@@ -16,6 +19,14 @@ use wgpu_glyph::{GlyphBrush, HorizontalAlign, VerticalAlign};
 use stretch::node::Node;
 use stretch::style::{Style, Dimension};
 use epaint::emath::{Rect, Pos2};
+use nice::{format_nice_label, NiceTicks};
+use overlap::{abbreviate_number, thinning, LabelBox, LabelOverlap};
+use time::{every_time_tick, TimeAxis};
+use wgpu_glyph::ab_glyph::{Font, PxScale, ScaleFont};
+
+pub mod nice;
+pub mod overlap;
+pub mod time;
 
 #[derive(Clone, Copy)]
 pub enum Orientation {
@@ -33,6 +44,14 @@ pub struct ScaleConfig {
     pub font_color: RGBA<f32>,
     pub font: FontArc,
     pub crosshair_background_color: Option<RGBA<f32>>,
+    pub axis_scale: AxisScale,
+    /// labels of log axes
+    pub log_label_format: LogLabelFormat,
+    /// x values are seconds of a time axis, ticks are placed at calendar units
+    pub time_axis: Option<TimeAxis>,
+    /// steps of linear axes
+    pub nice_ticks: NiceTicks,
+    pub label_overlap: LabelOverlap,
 }
 
 impl Default for ScaleConfig {
@@ -41,6 +60,11 @@ impl Default for ScaleConfig {
             max_ticks: 50,
             size: 50,
             font: FontArc::try_from_slice(include_bytes!("Inconsolata-Regular.ttf")).unwrap(),
+            axis_scale: AxisScale::Linear,
+            log_label_format: LogLabelFormat::Exponent,
+            time_axis: None,
+            nice_ticks: NiceTicks::default(),
+            label_overlap: LabelOverlap::Thin,
         }
     }
 }
@@ -53,6 +77,19 @@ pub struct Scale {
     text_style: TextStyle,
     sections: Vec<OwnedSection>,
     orientation: Orientation,
+    axis_scale: AxisScale,
+    log_label_format: LogLabelFormat,
+    time_axis: Option<TimeAxis>,
+    nice_ticks: NiceTicks,
+    font: FontArc,
+    font_size: Vec2,
+    /// pixels of the widest label on a linear axis, spacing of the next update starts from it
+    widest_label: f32,
+    label_overlap: LabelOverlap,
+    /// every n-th label is shown
+    thinning: usize,
+    /// ticks of the last update, labelled or not
+    ticks: Vec<Tick>,
     write_options: lexical::write_float_options::Options,
 }
 
@@ -62,8 +99,8 @@ impl Scale {
         match self.orientation {
             Orientation::Horizontal => {
                 let mut i = 0;
-                every_log10_x(state, |pos| {
-                    let text = lexical::to_string_with_options::<_, FORMAT>(pos.value, &write_options);
+                for pos in self.ticks.iter().filter(|x| x.major) {
+                    let text = self.sections[i].text[0].text.clone();
                     drawer.text_single_line(self.text_style, text, pos., self.text_color)
                     self.x_sections[i].screen_position = (
                         (pos.screen_pos + 1.) / state.pixel_scale.x,
@@ -71,7 +108,7 @@ impl Scale {
                         1.95 / state.pixel_scale.y,
                     );
                     i += 1;
-                });
+                }
                 for section in self.sections.iter_mut().skip(i) {
                     section.text[0].text.clear();
                 }
@@ -84,12 +121,7 @@ impl Scale {
     }
 
     pub fn new(config: &ScaleConfig, device: &Device, this: Node) -> (Self, i8) {
-        let border_ubd = [LineUniform {
-            color: config.border_color,
-            scale: Vec2::new(1., 1.),
-            translate: Vec2::new(0.0, 0.0),
-            line_scale: Vec2::new(0.01, 0.01),
-        }];
+        let border_ubd = [LineUniform::new(config.border_color, &LineStyle::default())];
         let border_ubo = Buffer::new(
             &device,
             BufferUsages::UNIFORM | BufferUsages::COPY_DST,
@@ -124,6 +156,16 @@ impl Scale {
                 },
                 sections,
                 orientation: config.orientation,
+                axis_scale: config.axis_scale,
+                log_label_format: config.log_label_format,
+                time_axis: config.time_axis,
+                nice_ticks: config.nice_ticks.clone(),
+                font: config.font.clone(),
+                font_size: config.font_size,
+                widest_label: 0.,
+                label_overlap: config.label_overlap,
+                thinning: 1,
+                ticks: Vec::new(),
                 border_width: config.border_width,
                 border_ubd,
                 border_vbd,
@@ -142,18 +184,17 @@ impl Scale {
         )
     }
 
+    /// Data value at screen space `pos` along the axis.
     pub fn get_value_at(&self, state: &ChartState, pos: f32) -> f32 {
-        match self.orientation {
-            Orientation::Horizontal => {
-                (-1. - state.translate.x + state.position.x * state.pixel_scale.x) / state.scale.x
-            }
-            Orientation::Vertical => {
-                (-1. - state.translate.y + state.position.y * state.pixel_scale.y) / state.scale.y
-            }
-        }
+        let axis = self.orientation as usize;
+        let value = (pos - state.translate[axis]) / state.scale[axis];
+        self.axis_scale.inverse(value)
     }
 
     pub fn convert_to_text(&self, value: f32) -> String {
+        if let Some(axis) = &self.time_axis {
+            return axis.format(value);
+        }
         lexical::to_string_with_options::<_, FORMAT>(value, &self.write_options)
     }
 
@@ -165,65 +206,163 @@ impl Scale {
         self.orientation
     }
 
-    fn update_x_ticks(
-        &mut self,
-        state: &ChartState,
-        write_options: &lexical::write_float_options::Options,
-    ) {
-        let mut i = 0;
-        every_log10_x(state, |pos| {
-            self.x_sections[i].screen_position = (
-                (pos.screen_pos + 1.) / state.pixel_scale.x,
-                1.95 / state.pixel_scale.y,
-            );
-            self.x_sections[i].text[0].text =
-                lexical::to_string_with_options::<_, FORMAT>(pos.value, &write_options);
-            i += 1;
+    pub fn axis_scale(&self) -> AxisScale {
+        self.axis_scale
+    }
+
+    pub fn ticks(&self) -> &[Tick] {
+        &self.ticks
+    }
+
+    pub fn time_axis(&self) -> Option<&TimeAxis> {
+        self.time_axis.as_ref()
+    }
+
+    pub fn nice_ticks(&self) -> &NiceTicks {
+        &self.nice_ticks
+    }
+
+    /// Pixels that `text` takes along the axis, width for horizontal scales and line height for
+    /// vertical ones.
+    pub fn label_extent(&self, text: &str) -> f32 {
+        let font = self.font.as_scaled(PxScale {
+            x: self.font_size.x,
+            y: self.font_size.y,
         });
-        for section in self.sections.iter_mut().skip(i) {
-            section.text[0].text.clear();
+        match self.orientation {
+            Orientation::Horizontal => {
+                text.chars().map(|x| font.h_advance(font.glyph_id(x))).sum()
+            }
+            Orientation::Vertical => font.height(),
         }
     }
 
-    fn update_y_ticks(
-        &mut self,
-        state: &ChartState,
-        write_options: &lexical::write_float_options::Options,
-    ) {
+    /// Labels decades, minor ticks are only kept in `ticks`.
+    fn update_log_ticks(&mut self, state: &ChartState, base: f32) {
+        let ticks = &mut self.ticks;
+        ticks.clear();
+        every_decade(state, self.orientation, base, self.sections.len(), |tick| {
+            ticks.push(tick)
+        });
+        let format = self.log_label_format;
+        let labels = self
+            .ticks
+            .iter()
+            .filter(|x| x.major)
+            .map(|x| format_log_label(x.value, base, format))
+            .collect();
+        self.place_labels(state, labels);
+    }
+
+    fn update_time_ticks(&mut self, state: &ChartState, axis: TimeAxis) {
+        let spacing = self.label_extent(TIME_LABEL_SAMPLE) + self.nice_ticks.padding;
+        let ticks = &mut self.ticks;
+        let mut labels = Vec::new();
+        ticks.clear();
+        every_time_tick(state, self.orientation, &axis, spacing, |tick, label| {
+            ticks.push(tick);
+            labels.push(label);
+        });
+        self.place_labels(state, labels);
+    }
+
+    /// Moves labels to major ticks in order, labels that would overlap are thinned out and unused
+    /// sections are cleared.
+    fn place_labels(&mut self, state: &ChartState, labels: Vec<String>) {
+        let orientation = self.orientation;
+        let center = |tick: &Tick| match orientation {
+            Orientation::Horizontal => (tick.screen_pos + 1.) / state.pixel_scale.x,
+            Orientation::Vertical => (1. - tick.screen_pos) / state.pixel_scale.y,
+        };
+        let boxes: Vec<_> = self
+            .ticks
+            .iter()
+            .filter(|x| x.major)
+            .zip(&labels)
+            .map(|(tick, label)| LabelBox {
+                index: tick.index,
+                center: center(tick),
+                extent: self.label_extent(label),
+            })
+            .collect();
+        let factor = thinning(&boxes, self.nice_ticks.padding, self.thinning);
+        self.thinning = factor;
+        let majors = self
+            .ticks
+            .iter()
+            .filter(|x| x.major)
+            .zip(labels)
+            .filter(|(tick, _)| tick.index.rem_euclid(factor as i64) == 0);
         let mut i = 0;
-        every_log10_y(state, |pos| {
-            self.y_sections[i].screen_position = (
-                (pos.screen_pos + 1.) / state.piyel_scale.y,
-                1.95 / state.piyel_scale.y,
-            );
-            self.y_sections[i].teyt[0].teyt =
-                lexical::to_string_with_options::<_, FORMAT>(pos.value, &write_options);
+        for ((tick, label), section) in majors.zip(self.sections.iter_mut()) {
+            section.screen_position = match orientation {
+                Orientation::Horizontal => (center(tick), 1.95 / state.pixel_scale.y),
+                Orientation::Vertical => (0., center(tick)),
+            };
+            section.text[0].text = label;
             i += 1;
-        });
+        }
         for section in self.sections.iter_mut().skip(i) {
             section.text[0].text.clear();
         }
     }
+
+    /// Labels on linear axes are measured with the step of the previous update, if the widest one
+    /// doesn't fit the spacing ticks are generated again.
+    fn update_linear_ticks(&mut self, state: &ChartState) {
+        let mut extent = self.widest_label.max(self.label_extent("0"));
+        loop {
+            let ticks = &mut self.ticks;
+            ticks.clear();
+            let step = self
+                .nice_ticks
+                .generate(state, self.orientation, extent, |tick| ticks.push(tick));
+            let labels: Vec<_> = self
+                .ticks
+                .iter()
+                .filter(|x| x.major)
+                .map(|x| match self.label_overlap {
+                    LabelOverlap::Thin => format_nice_label(x.value, step),
+                    LabelOverlap::Abbreviate => abbreviate_number(x.value, step),
+                })
+                .collect();
+            let widest = labels
+                .iter()
+                .map(|x| self.label_extent(x))
+                .fold(0., f32::max);
+            if widest <= extent {
+                self.widest_label = widest;
+                return self.place_labels(state, labels);
+            }
+            extent = widest;
+        }
+    }
+
+    fn update_ticks(&mut self, state: &ChartState) {
+        if let Some(axis) = self.time_axis {
+            return self.update_time_ticks(state, axis);
+        }
+        match self.axis_scale {
+            AxisScale::Log(base) => self.update_log_ticks(state, base),
+            AxisScale::Linear => self.update_linear_ticks(state),
+        }
+    }
 }
 
 impl Component for Scale {
     fn layout_invalidated(&mut self, state: &ChartState) {
         self.border_ubd[0].line_scale.x = self.border_width as f32 * state.pixel_scale.x;
         self.border_ubd[0].line_scale.y = self.border_width as f32 * state.pixel_scale.y;
+        // ticks are placed in pixels so they are needed before the first pan or zoom
+        self.update_ticks(state);
     }
 
     fn on_zoom(&mut self, state: &ChartState) {
-        match self.orientation {
-            Orientation::Horizontal => self.update_x_ticks(state, &self.write_options),
-            Orientation::Vertical => self.update_y_ticks(state, &self.write_options),
-        }
+        self.update_ticks(state);
     }
 
     fn on_pan(&mut self, state: &ChartState) {
-        match self.orientation {
-            Orientation::Horizontal => self.update_x_ticks(state, &self.write_options),
-            Orientation::Vertical => self.update_y_ticks(state, &self.write_options),
-        }
+        self.update_ticks(state);
     }
 
     fn draw_line_strip<'s, 'e>(
@@ -249,66 +388,107 @@ impl Layoutable for Scale {
     }
 }
 
-pub struct Log10Pos {
-    screen_pos: f32,
-    value: f32,
-}
+/// Widest common label of a time axis, ticks are spaced so that it fits.
+const TIME_LABEL_SAMPLE: &str = "00:00:00";
 
-pub fn every_log10_x(state: &ChartState, callback: impl FnMut(Log10Pos)) {
-    let mut log = 10.0f32.powf(-state.scale.x.log10().floor()) / 100.;
-    let start = (-1. - state.translate.x + state.position.x * state.pixel_scale.x) / state.scale.x;
-    let mut x_value = if start > 0. {
-        start - start.rem(log) + log
-    } else {
-        start - start.rem(log)
-    };
-    let mut x = x_value * state.scale.x + state.translate.x;
-    let mut tick_spacing = log * state.scale.x;
-    if 2. / tick_spacing > 50. {
-        log *= 10.;
-        tick_spacing *= 10.;
-        x_value = start - start.rem(log);
-        x = x_value * state.scale.x + state.translate.x;
-    }
+/// Minimum distance in pixels between minor ticks of a log axis.
+const MIN_MINOR_TICK_SPACING: f32 = 4.;
 
-    while x < 1. {
-        callback(Log10Pos {
-            screen_pos: x,
-            value: x_value,
-        });
+#[derive(Clone, Copy, Debug)]
+pub struct Tick {
+    pub screen_pos: f32,
+    pub value: f32,
+    /// minor ticks are not labelled
+    pub major: bool,
+    /// index of the major tick at or before this one counted from 0 in steps of the axis, stays
+    /// the same when panning
+    pub index: i64,
+}
 
-        x += tick_spacing;
-        x_value += log;
-    }
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogLabelFormat {
+    /// `1e3`, bases other than 10 are written as `2^10`
+    Exponent,
+    /// `1k`, `10M` or `100m`, falls back to `Exponent` outside of SI prefixes
+    SiPrefix,
 }
 
-pub fn every_log10_y(state: &ChartState, callback: impl FnMut(Log10Pos)) {
-    let mut log = 10.0f32.powf(-state.scale.y.log10().floor()) / 100.;
-    let start = (-1. - state.translate.y + state.position.y * state.pixel_scale.y) / state.scale.y;
-    let mut y_value = if start > 0. {
-        start - start.rem(log) + log
+/// Ticks of a log axis that are in view, major ones at powers of `base` and minor ones at 2 to
+/// `base - 1` times them if they are far enough apart.
+/// If there are more than `max_major` decades only every n-th is a tick, n is a multiple of the
+/// exponent so that ticks don't jump around when panning.
+pub fn every_decade(
+    state: &ChartState,
+    orientation: Orientation,
+    base: f32,
+    max_major: usize,
+    mut callback: impl FnMut(Tick),
+) {
+    let axis = orientation as usize;
+    let scale = state.scale[axis];
+    let translate = state.translate[axis];
+    let start = -1. + state.position[axis] * state.pixel_scale[axis];
+    let end = 1.;
+    // visible range in exponents
+    let lo = (start - translate) / scale;
+    let hi = (end - translate) / scale;
+    let first = lo.min(hi).floor() as i32;
+    let last = lo.max(hi).ceil() as i32;
+    let decades = (last - first).max(1) as usize;
+    let step = ((decades + max_major - 1) / max_major.max(1)).max(1) as i32;
+    let decade_pixels = scale.abs() / state.pixel_scale[axis];
+    // narrowest gap is between `base - 1` and `base`
+    let minor_gap = (base / (base - 1.)).log(base) * decade_pixels;
+    let multiples = if step == 1 && base.fract() == 0. && minor_gap >= MIN_MINOR_TICK_SPACING {
+        base as u32 - 1
     } else {
-        start - start.rem(log)
+        1
     };
-    let mut y = y_value * state.scale.y + state.translate.y;
-    let mut tick_spacing = log * state.scale.y;
-    if 2. / tick_spacing > 50. {
-        log *= 10.;
-        tick_spacing *= 10.;
-        y_value = start - start.rem(log);
-        y = y_value * state.scale.y + state.translate.y;
+    let mut exponent = first - first.rem_euclid(step);
+    while exponent <= last {
+        for m in 1..=multiples {
+            let screen_pos = (exponent as f32 + (m as f32).log(base)) * scale + translate;
+            if screen_pos >= start && screen_pos <= end {
+                callback(Tick {
+                    screen_pos,
+                    value: m as f32 * base.powi(exponent),
+                    major: m == 1,
+                    index: exponent.div_euclid(step) as i64,
+                });
+            }
+        }
+        exponent += step;
     }
+}
 
-    let n_y_ticks = 2. / tick_spacing;
-    while y < 1. {
-        callback(Log10Pos {
-            screen_pos: y,
-            value: y_value,
-        });
+/// Label of a power of `base`.
+pub fn format_log_label(value: f32, base: f32, format: LogLabelFormat) -> String {
+    const SI_PREFIXES: [&str; 11] = ["f", "p", "n", "µ", "m", "", "k", "M", "G", "T", "P"];
+    let exponent = value.log(base).round() as i32;
+    if base != 10. {
+        return format!("{}^{}", format_log_base(base), exponent);
+    }
+    let group = exponent.div_euclid(3);
+    if format == LogLabelFormat::SiPrefix && (-5..=5).contains(&group) {
+        let mantissa = 10u32.pow(exponent.rem_euclid(3) as u32);
+        return format!("{}{}", mantissa, SI_PREFIXES[(group + 5) as usize]);
+    }
+    if exponent == 0 {
+        return "1".to_string();
+    }
+    format!("1e{}", exponent)
+}
 
-        y += tick_spacing;
-        y_value += log;
+/// `e` for the natural logarithm, other bases with at most 3 decimals.
+fn format_log_base(base: f32) -> String {
+    if (base - std::f32::consts::E).abs() < 1e-6 {
+        return "e".to_string();
     }
+    let formatted = format!("{:.3}", base);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
 }
 
 fn section_builder<T>(orientation: Orientation) -> impl FnMut(T) -> OwnedSection {
@@ -0,0 +1,126 @@
+use crate::components::scale::{Orientation, Tick};
+use crate::components::ChartState;
+
+/// Step of a linear axis, `mantissa` times a power of 10, split into `minor` parts by minor ticks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NiceStep {
+    pub mantissa: f64,
+    pub minor: u32,
+}
+
+impl NiceStep {
+    pub fn new(mantissa: f64, minor: u32) -> Self {
+        Self { mantissa, minor }
+    }
+}
+
+/// Picks ticks of a linear axis at multiples of nice numbers, eg. 1, 2 and 5 times a power of 10.
+/// Majors are the smallest step whose ticks are at least label size and `padding` pixels apart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NiceTicks {
+    /// mantissas in `1..10` in ascending order
+    pub steps: Vec<NiceStep>,
+    /// pixels between labels
+    pub padding: f32,
+    /// minor ticks are skipped if they would be closer than this many pixels
+    pub min_minor_spacing: f32,
+}
+
+impl NiceTicks {
+    pub fn new(steps: Vec<NiceStep>) -> Self {
+        assert!(!steps.is_empty(), "tick generator needs at least one step");
+        Self {
+            steps,
+            ..Default::default()
+        }
+    }
+
+    /// Smallest step in data units that is at least `spacing` pixels wide, also returns the
+    /// number of minor parts.
+    pub fn step(&self, pixels_per_unit: f32, spacing: f32) -> (f64, u32) {
+        let raw = (spacing.max(1.) / pixels_per_unit) as f64;
+        if !raw.is_finite() || raw <= 0. {
+            return (1., 1);
+        }
+        let power = 10f64.powf(raw.log10().floor());
+        self.steps
+            .iter()
+            .map(|x| (x.mantissa * power, x.minor))
+            .find(|(step, _)| *step >= raw * (1. - 1e-9))
+            .unwrap_or((self.steps[0].mantissa * power * 10., self.steps[0].minor))
+    }
+
+    /// Ticks that are in view for labels `label_size` pixels along the axis, returns the major
+    /// step. Ticks are multiples of the step so they stay in place when panning.
+    pub fn generate(
+        &self,
+        state: &ChartState,
+        orientation: Orientation,
+        label_size: f32,
+        mut callback: impl FnMut(Tick),
+    ) -> f64 {
+        let axis = orientation as usize;
+        let scale = state.scale[axis];
+        let translate = state.translate[axis];
+        let start = -1. + state.position[axis] * state.pixel_scale[axis];
+        let end = 1.;
+        let lo = ((start - translate) / scale) as f64;
+        let hi = ((end - translate) / scale) as f64;
+        let pixels_per_unit = scale.abs() / state.pixel_scale[axis];
+        let (step, minor) = self.step(pixels_per_unit, label_size + self.padding);
+        let minor_pixels = step as f32 * pixels_per_unit / minor.max(1) as f32;
+        let parts = if minor_pixels >= self.min_minor_spacing {
+            minor.max(1) as i64
+        } else {
+            1
+        };
+        let minor_step = step / parts as f64;
+        // indices of minor ticks, majors are every `parts`-th so they are multiples of `step`
+        let first = (lo.min(hi) / minor_step).ceil() as i64;
+        let last = (lo.max(hi) / minor_step).floor() as i64;
+        for i in first..=last {
+            let value = (i as f64 * minor_step) as f32;
+            let screen_pos = value * scale + translate;
+            if screen_pos >= start && screen_pos <= end {
+                callback(Tick {
+                    screen_pos,
+                    value,
+                    major: i.rem_euclid(parts) == 0,
+                    index: i.div_euclid(parts),
+                });
+            }
+        }
+        step
+    }
+}
+
+impl Default for NiceTicks {
+    /// 1-2-5 steps.
+    fn default() -> Self {
+        Self {
+            steps: vec![
+                NiceStep::new(1., 5),
+                NiceStep::new(2., 4),
+                NiceStep::new(5., 5),
+            ],
+            padding: 16.,
+            min_minor_spacing: 4.,
+        }
+    }
+}
+
+/// Label of a multiple of `step` with as many decimals as the step needs.
+pub fn format_nice_label(value: f32, step: f64) -> String {
+    let mut decimals = 0;
+    let mut scaled = step;
+    while decimals < 12 && (scaled - scaled.round()).abs() > 1e-6 * scaled {
+        decimals += 1;
+        scaled *= 10.;
+    }
+    let label = format!("{:.*}", decimals, value);
+    // -0.0 happens for values that are close to 0
+    match label.strip_prefix('-') {
+        Some(x) if x.bytes().all(|x| x == b'0' || x == b'.') => x.to_string(),
+        _ => label,
+    }
+}
@@ -0,0 +1,69 @@
+use crate::components::scale::nice::format_nice_label;
+
+/// How a `Scale` keeps labels from overlapping, labels that still overlap are thinned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LabelOverlap {
+    /// keep only every n-th label
+    Thin,
+    /// write numbers of linear axes with SI suffixes, eg. `12.5k`, so that they fit more densely
+    Abbreviate,
+}
+
+/// Label of a major tick measured in pixels.
+#[derive(Clone, Copy, Debug)]
+pub struct LabelBox {
+    /// `Tick::index`
+    pub index: i64,
+    /// center along the axis
+    pub center: f32,
+    /// size along the axis
+    pub extent: f32,
+}
+
+/// Labels with an index that is a multiple of the returned factor are shown, factors are 1, 2, 5,
+/// 10, 20 and so on.
+/// Indices don't depend on what's in view so the same labels stay when panning, `previous` factor
+/// is kept until labels fit with room to spare so that it doesn't flip back and forth.
+pub fn thinning(labels: &[LabelBox], padding: f32, previous: usize) -> usize {
+    let mut factor = 1;
+    let mut i = 0;
+    while factor < labels.len() && !fits(labels, factor, padding) {
+        factor = [1, 2, 5][(i + 1) % 3] * 10usize.pow((i + 1) as u32 / 3);
+        i += 1;
+    }
+    if factor < previous && !fits(labels, factor, padding * 2.) {
+        previous
+    } else {
+        factor
+    }
+}
+
+/// Whether every `factor`-th label has at least `padding` pixels to its neighbours.
+pub fn fits(labels: &[LabelBox], factor: usize, padding: f32) -> bool {
+    let mut shown = labels
+        .iter()
+        .filter(|x| x.index.rem_euclid(factor as i64) == 0);
+    let mut prev = match shown.next() {
+        Some(x) => x,
+        None => return true,
+    };
+    for label in shown {
+        if (label.center - prev.center).abs() < (label.extent + prev.extent) / 2. + padding {
+            return false;
+        }
+        prev = label;
+    }
+    true
+}
+
+/// Multiple of `step` with thousands written as SI suffixes, eg. `1.5M`.
+pub fn abbreviate_number(value: f32, step: f64) -> String {
+    const SUFFIXES: [&str; 5] = ["", "k", "M", "G", "T"];
+    let group = ((value.abs().log10() / 3.).floor() as i32).max(0).min(4);
+    if group == 0 {
+        return format_nice_label(value, step);
+    }
+    let divisor = 1000f64.powi(group);
+    let label = format_nice_label((value as f64 / divisor) as f32, step / divisor);
+    label + SUFFIXES[group as usize]
+}
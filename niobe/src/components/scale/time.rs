@@ -0,0 +1,350 @@
+use crate::components::scale::{Orientation, Tick};
+use crate::components::ChartState;
+
+const SECOND: i64 = 1000;
+const MINUTE: i64 = 60 * SECOND;
+const HOUR: i64 = 60 * MINUTE;
+const DAY: i64 = 24 * HOUR;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Unit of epoch timestamps.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EpochUnit {
+    Seconds,
+    Milliseconds,
+    Nanoseconds,
+}
+
+impl EpochUnit {
+    fn per_second(self) -> i64 {
+        match self {
+            EpochUnit::Seconds => 1,
+            EpochUnit::Milliseconds => 1_000,
+            EpochUnit::Nanoseconds => 1_000_000_000,
+        }
+    }
+}
+
+/// Maps epoch timestamps to data units, which are seconds since `origin`.
+/// f32 can't hold epoch timestamps precisely so data is relative to an origin that should be
+/// close to the data, eg. the first timestamp.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeAxis {
+    pub unit: EpochUnit,
+    /// timestamp in `unit` that is 0 in data units
+    pub origin: i64,
+}
+
+impl TimeAxis {
+    pub fn new(unit: EpochUnit, origin: i64) -> Self {
+        Self { unit, origin }
+    }
+
+    /// Timestamp in `unit` to data units.
+    pub fn to_data(&self, timestamp: i64) -> f32 {
+        ((timestamp - self.origin) as f64 / self.unit.per_second() as f64) as f32
+    }
+
+    /// Data units to timestamp in `unit`.
+    pub fn from_data(&self, value: f32) -> i64 {
+        self.origin + (value as f64 * self.unit.per_second() as f64).round() as i64
+    }
+
+    /// Date and time of a value in data units, eg. for crosshair labels.
+    pub fn format(&self, value: f32) -> String {
+        let date = DateTime::from_millis(self.to_millis(value));
+        format!(
+            "{} {} {} {:02}:{:02}:{:02}",
+            date.day,
+            MONTHS[date.month as usize - 1],
+            date.year,
+            date.hour(),
+            date.minute(),
+            date.second()
+        )
+    }
+
+    fn to_millis(&self, value: f32) -> i64 {
+        let origin = self.origin as i128 * 1000 / self.unit.per_second() as i128;
+        origin as i64 + (value as f64 * 1000.).round() as i64
+    }
+
+    fn from_millis(&self, millis: i64) -> f32 {
+        let origin = self.origin as f64 * 1000. / self.unit.per_second() as f64;
+        ((millis as f64 - origin) / 1000.) as f32
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimeUnit {
+    Millisecond,
+    Second,
+    Minute,
+    Hour,
+    Day,
+    Week,
+    Month,
+    Quarter,
+    Year,
+}
+
+impl TimeUnit {
+    /// Length in milliseconds, approximate for months, quarters and years.
+    fn millis(self) -> i64 {
+        match self {
+            TimeUnit::Millisecond => 1,
+            TimeUnit::Second => SECOND,
+            TimeUnit::Minute => MINUTE,
+            TimeUnit::Hour => HOUR,
+            TimeUnit::Day => DAY,
+            TimeUnit::Week => 7 * DAY,
+            TimeUnit::Month => 30 * DAY,
+            TimeUnit::Quarter => 91 * DAY,
+            TimeUnit::Year => 365 * DAY,
+        }
+    }
+}
+
+/// Tick steps from the finest to the coarsest, steps of a unit divide the next unit evenly.
+const STEPS: &[(TimeUnit, i64)] = &[
+    (TimeUnit::Millisecond, 1),
+    (TimeUnit::Millisecond, 2),
+    (TimeUnit::Millisecond, 5),
+    (TimeUnit::Millisecond, 10),
+    (TimeUnit::Millisecond, 20),
+    (TimeUnit::Millisecond, 50),
+    (TimeUnit::Millisecond, 100),
+    (TimeUnit::Millisecond, 200),
+    (TimeUnit::Millisecond, 500),
+    (TimeUnit::Second, 1),
+    (TimeUnit::Second, 2),
+    (TimeUnit::Second, 5),
+    (TimeUnit::Second, 10),
+    (TimeUnit::Second, 15),
+    (TimeUnit::Second, 30),
+    (TimeUnit::Minute, 1),
+    (TimeUnit::Minute, 2),
+    (TimeUnit::Minute, 5),
+    (TimeUnit::Minute, 10),
+    (TimeUnit::Minute, 15),
+    (TimeUnit::Minute, 30),
+    (TimeUnit::Hour, 1),
+    (TimeUnit::Hour, 3),
+    (TimeUnit::Hour, 6),
+    (TimeUnit::Hour, 12),
+    (TimeUnit::Day, 1),
+    (TimeUnit::Day, 2),
+    (TimeUnit::Week, 1),
+    (TimeUnit::Month, 1),
+    (TimeUnit::Quarter, 1),
+    (TimeUnit::Year, 1),
+    (TimeUnit::Year, 2),
+    (TimeUnit::Year, 5),
+    (TimeUnit::Year, 10),
+    (TimeUnit::Year, 20),
+    (TimeUnit::Year, 50),
+    (TimeUnit::Year, 100),
+];
+
+/// Finest step whose ticks are at least `min_spacing` pixels apart, `pixels_per_ms` is the zoom.
+pub fn time_step(pixels_per_ms: f32, min_spacing: f32) -> (TimeUnit, i64) {
+    STEPS
+        .iter()
+        .copied()
+        .find(|(unit, count)| (unit.millis() * count) as f32 * pixels_per_ms >= min_spacing)
+        .unwrap_or(STEPS[STEPS.len() - 1])
+}
+
+/// Ticks of a time axis that are in view, every tick is labelled.
+/// Labels are hierarchical, a tick where a coarser unit changes is labelled with that unit eg.
+/// "09:30" and "Mar 3" at midnight. All times are UTC.
+pub fn every_time_tick(
+    state: &ChartState,
+    orientation: Orientation,
+    axis: &TimeAxis,
+    min_spacing: f32,
+    mut callback: impl FnMut(Tick, String),
+) {
+    let i = orientation as usize;
+    let scale = state.scale[i];
+    let translate = state.translate[i];
+    let start = -1. + state.position[i] * state.pixel_scale[i];
+    let end = 1.;
+    let lo = (start - translate) / scale;
+    let hi = (end - translate) / scale;
+    let first = axis.to_millis(lo.min(hi));
+    let last = axis.to_millis(lo.max(hi));
+    // one data unit is a second
+    let pixels_per_ms = scale.abs() / state.pixel_scale[i] / 1000.;
+    let (unit, count) = time_step(pixels_per_ms, min_spacing);
+    let mut t = floor_time(first, unit, count);
+    let mut prev = None;
+    while t <= last {
+        let date = DateTime::from_millis(t);
+        let value = axis.from_millis(t);
+        let screen_pos = value * scale + translate;
+        if screen_pos >= start && screen_pos <= end {
+            let tick = Tick {
+                screen_pos,
+                value,
+                major: true,
+                index: step_index(t, unit, count),
+            };
+            callback(tick, label(&date, prev.as_ref(), unit));
+        }
+        prev = Some(date);
+        t = add_time(t, unit, count);
+    }
+}
+
+fn label(date: &DateTime, prev: Option<&DateTime>, unit: TimeUnit) -> String {
+    let changed = |f: fn(&DateTime) -> i64| prev.map_or(false, |x| f(x) != f(date));
+    let year_changed = changed(|x| x.year);
+    let month_changed = changed(|x| x.year * 12 + x.month as i64);
+    let day_changed = changed(|x| x.days);
+    let second_changed = changed(|x| x.millis_of_day / SECOND);
+    match unit {
+        TimeUnit::Year => format!("{}", date.year),
+        TimeUnit::Month | TimeUnit::Quarter if year_changed => format!("{}", date.year),
+        TimeUnit::Month | TimeUnit::Quarter => MONTHS[date.month as usize - 1].to_string(),
+        TimeUnit::Day | TimeUnit::Week if year_changed => format!("{}", date.year),
+        TimeUnit::Day | TimeUnit::Week if month_changed => {
+            MONTHS[date.month as usize - 1].to_string()
+        }
+        TimeUnit::Day | TimeUnit::Week => format!("{}", date.day),
+        _ if year_changed => format!("{}", date.year),
+        _ if day_changed => format!("{} {}", MONTHS[date.month as usize - 1], date.day),
+        TimeUnit::Hour | TimeUnit::Minute => {
+            format!("{:02}:{:02}", date.hour(), date.minute())
+        }
+        TimeUnit::Millisecond if !second_changed => {
+            format!("{:02}.{:03}", date.second(), date.millis_of_day % SECOND)
+        }
+        _ => format!(
+            "{:02}:{:02}:{:02}",
+            date.hour(),
+            date.minute(),
+            date.second()
+        ),
+    }
+}
+
+/// Start of the step that `t` is in. Steps of months, quarters and years are counted from year 0
+/// so they are aligned to years, weeks start on Monday and shorter units are counted from the
+/// epoch, eg. steps of 2 days don't restart on the first day of a month.
+fn floor_time(t: i64, unit: TimeUnit, count: i64) -> i64 {
+    match unit {
+        TimeUnit::Week => {
+            // 1970-01-01 is a Thursday, weeks start on Monday
+            let days = t.div_euclid(DAY);
+            (days - (days + 3).rem_euclid(7)) * DAY
+        }
+        TimeUnit::Month | TimeUnit::Quarter | TimeUnit::Year => {
+            let date = DateTime::from_millis(t);
+            let months = date.year * 12 + date.month as i64 - 1;
+            let step = months_in(unit) * count;
+            let months = months - months.rem_euclid(step);
+            days_from_civil(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1) * DAY
+        }
+        _ => {
+            let step = unit.millis() * count;
+            t - t.rem_euclid(step)
+        }
+    }
+}
+
+fn add_time(t: i64, unit: TimeUnit, count: i64) -> i64 {
+    match unit {
+        TimeUnit::Month | TimeUnit::Quarter | TimeUnit::Year => {
+            let date = DateTime::from_millis(t);
+            let months = date.year * 12 + date.month as i64 - 1 + months_in(unit) * count;
+            days_from_civil(months.div_euclid(12), months.rem_euclid(12) as u32 + 1, 1) * DAY
+        }
+        _ => t + unit.millis() * count,
+    }
+}
+
+/// Number of steps since epoch.
+fn step_index(t: i64, unit: TimeUnit, count: i64) -> i64 {
+    match unit {
+        TimeUnit::Week => (t.div_euclid(DAY) + 3).div_euclid(7) / count,
+        TimeUnit::Month | TimeUnit::Quarter | TimeUnit::Year => {
+            let date = DateTime::from_millis(t);
+            (date.year * 12 + date.month as i64 - 1).div_euclid(months_in(unit) * count)
+        }
+        _ => t.div_euclid(unit.millis() * count),
+    }
+}
+
+fn months_in(unit: TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Quarter => 3,
+        TimeUnit::Year => 12,
+        _ => 1,
+    }
+}
+
+/// UTC calendar date of a timestamp in milliseconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    /// days since epoch
+    pub days: i64,
+    pub millis_of_day: i64,
+}
+
+impl DateTime {
+    pub fn from_millis(t: i64) -> Self {
+        let days = t.div_euclid(DAY);
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year,
+            month,
+            day,
+            days,
+            millis_of_day: t.rem_euclid(DAY),
+        }
+    }
+
+    pub fn hour(&self) -> i64 {
+        self.millis_of_day / HOUR
+    }
+
+    pub fn minute(&self) -> i64 {
+        self.millis_of_day % HOUR / MINUTE
+    }
+
+    pub fn second(&self) -> i64 {
+        self.millis_of_day % MINUTE / SECOND
+    }
+}
+
+// see: http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = year - (month <= 2) as i64;
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = month as i64;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
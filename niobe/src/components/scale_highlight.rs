@@ -12,8 +12,11 @@ use wgpu::util::RenderEncoder;
 use wgpu::{BufferUsages, Device};
 use wgpu_glyph::GlyphBrush;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum ScaleHighlightKind {
     FollowMouse,
+    /// moved by a `Crosshair` with `ScaleHighlight::move_to`
+    FollowCrosshair,
 }
 
 pub struct ScaleHighlightConfig {
@@ -21,7 +24,10 @@ pub struct ScaleHighlightConfig {
 }
 
 pub struct ScaleHighlight {
+    kind: ScaleHighlightKind,
     scale: Rc<RefCell<Scale>>,
+    /// screen space position that is highlighted
+    pos: Vec2,
     section: OwnedSection,
     mesh_group: MeshBindGroup,
     mesh_ebo: Buffer<Point2d>,
@@ -42,6 +48,7 @@ impl ScaleHighlight {
             scale: Vec2::new(1., 1.),
             translate: Vec2::new(0., 0.),
             mesh_scale: Vec2::new(1., 1.),
+            log_base: Vec2::new(0., 0.),
         }];
         let mesh_ubo = Buffer::new(
             &device,
@@ -60,6 +67,8 @@ impl ScaleHighlight {
 
         (
             Self {
+                kind: config.kind,
+                pos: Vec2::new(0., 0.),
                 section: (scale.borrow().section_builder())(),
                 scale,
                 mesh_ubo,
@@ -73,10 +82,24 @@ impl ScaleHighlight {
         )
     }
 
+    /// Highlights the value at screen space `pos`.
+    pub fn move_to(&mut self, state: &ChartState, pos: Vec2) {
+        self.pos = pos;
+        self.mesh_ubd[0].translate = pos;
+        let orientation = self.scale.borrow().orientation();
+        match orientation {
+            Orientation::Horizontal => self.update_highlight_x_values(state),
+            Orientation::Vertical => self.update_highlight_y_values(state),
+        }
+        let orientation = orientation as usize;
+        self.mesh_ebd[0][orientation] = pos[orientation];
+        self.mesh_ebo.write_sliced(&self.queue, .., &self.mesh_ebd);
+    }
+
     fn update_highlight_x_values(&mut self, state: &ChartState) {
         let mut scale = self.scale.borrow();
-        let value = scale.get_value_at(&state, state.mouse_pos.x);
-        let pos = Vec2::new(state.mouse_pos.x, scale.layout().position.y);
+        let value = scale.get_value_at(&state, self.pos.x);
+        let pos = Vec2::new(self.pos.x, scale.layout().position.y);
         let pos = state.screen_to_pixel_space(pos);
         self.section.screen_position = (pos.x as f32, pos.y as f32);
         self.section.text[0].text = scale.convert_to_text(value);
@@ -84,8 +107,8 @@ impl ScaleHighlight {
 
     fn update_highlight_y_values(&mut self, state: &ChartState) {
         let mut scale = self.scale.borrow();
-        let value = scale.get_value_at(&state, state.mouse_pos.y);
-        let pos = Vec2::new(scale.layout().position.x, state.mouse_pos.y);
+        let value = scale.get_value_at(&state, self.pos.y);
+        let pos = Vec2::new(scale.layout().position.x, self.pos.y);
         let pos = state.screen_to_pixel_space(pos);
         self.section.screen_position = (pos.x as f32, pos.y as f32);
         self.section.text[0].text = scale.convert_to_text(value);
@@ -94,15 +117,9 @@ impl ScaleHighlight {
 
 impl Component for ScaleHighlight {
     fn on_mouse_moved(&mut self, state: &ChartState) {
-        self.mesh_ubd[0].translate = state.mouse_pos;
-        let orientation = self.scale.borrow().orientation();
-        match orientation {
-            Orientation::Horizontal => self.update_highlight_x_values(state),
-            Orientation::Vertical => self.update_highlight_y_values(state),
+        if self.kind == ScaleHighlightKind::FollowMouse {
+            self.move_to(state, state.mouse_pos);
         }
-        let orientation = orientation as usize;
-        self.mesh_ebd[0][orientation] = state.mouse_pos[orientation];
-        self.mesh_ebo.write_sliced(&self.queue, .., &self.mesh_ebd);
     }
 
     fn on_zoom(&mut self, state: &ChartState) {
@@ -0,0 +1,237 @@
+use crate::combo::ChartCombo;
+use crate::components::scale::Scale;
+use crate::components::{ChartState, Component};
+use crate::series::nearest::{nearest, SeriesPoints};
+use epaint::emath::{Pos2, Rect, Vec2};
+use epaint::{Color32, Stroke, TextStyle};
+use nalgebra_glm as glm;
+use niobe_core::pipelines::AxisScale;
+use niobe_core::{Point2d, UiPipeline};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use stretch::node::Node;
+
+#[derive(Clone, Debug)]
+pub struct TooltipConfig {
+    pub text_style: TextStyle,
+    pub text_color: Color32,
+    pub fill_color: Color32,
+    pub border: Stroke,
+    /// points farther from the cursor than this many pixels are not shown
+    pub max_distance: f32,
+    /// pixels between the cursor and the box
+    pub offset: f32,
+    pub padding: f32,
+    /// radius of the circle around the nearest point
+    pub marker_radius: f32,
+}
+
+impl Default for TooltipConfig {
+    fn default() -> Self {
+        Self {
+            text_style: TextStyle::Body,
+            text_color: Color32::from_gray(220),
+            fill_color: Color32::from_black_alpha(200),
+            border: Stroke::new(1., Color32::from_gray(90)),
+            max_distance: 32.,
+            offset: 12.,
+            padding: 6.,
+            marker_radius: 4.,
+        }
+    }
+}
+
+struct TooltipSeries {
+    name: String,
+    series: Rc<RefCell<dyn SeriesPoints>>,
+    /// eg. `visibility` of the series
+    visible: Option<Rc<Cell<bool>>>,
+}
+
+/// Point under the cursor and its formatted values.
+struct Hit {
+    /// pixels of the point
+    point: Pos2,
+    lines: Vec<String>,
+}
+
+/// Box with values of the data point nearest to the cursor across visible series of a `View`.
+/// Points are looked up with a binary search on x so series must be sorted by x.
+pub struct Tooltip {
+    view: Node,
+    config: TooltipConfig,
+    x_scale: Option<Rc<RefCell<Scale>>>,
+    y_scale: Option<Rc<RefCell<Scale>>>,
+    series: Vec<TooltipSeries>,
+    /// pixels of the cursor
+    mouse: Pos2,
+    hit: Option<Hit>,
+}
+
+impl Tooltip {
+    pub fn new(
+        config: &TooltipConfig,
+        view: Node,
+        x_scale: Option<Rc<RefCell<Scale>>>,
+        y_scale: Option<Rc<RefCell<Scale>>>,
+    ) -> Self {
+        Self {
+            view,
+            config: config.clone(),
+            x_scale,
+            y_scale,
+            series: Vec::new(),
+            mouse: Pos2::default(),
+            hit: None,
+        }
+    }
+
+    /// Series is skipped while `visible` is false.
+    pub fn add_series(
+        &mut self,
+        name: impl Into<String>,
+        series: Rc<RefCell<dyn SeriesPoints>>,
+        visible: Option<Rc<Cell<bool>>>,
+    ) {
+        self.series.push(TooltipSeries {
+            name: name.into(),
+            series,
+            visible,
+        });
+    }
+
+    fn update(&mut self, state: &ChartState) {
+        self.hit = None;
+        let to_pixels = |pos: glm::Vec2| {
+            glm::vec2(
+                (pos.x + 1.) / state.pixel_scale.x,
+                (1. - pos.y) / state.pixel_scale.y,
+            )
+        };
+        let mouse = to_pixels(state.mouse_pos);
+        self.mouse = Pos2::new(mouse.x, mouse.y);
+        let layout = state.flexbox.layout(self.view).unwrap();
+        let rect = Rect::from_min_size(
+            Pos2::new(layout.location.x, layout.location.y),
+            Vec2::new(layout.size.width, layout.size.height),
+        );
+        if !rect.contains(self.mouse) {
+            return;
+        }
+        let axis_scale = |x: &Option<Rc<RefCell<Scale>>>| {
+            x.as_ref()
+                .map_or(AxisScale::Linear, |x| x.borrow().axis_scale())
+        };
+        let (x_scale, y_scale) = (axis_scale(&self.x_scale), axis_scale(&self.y_scale));
+        let to_data = |pos: glm::Vec2| {
+            Point2d::new(
+                x_scale.inverse((pos.x - state.translate.x) / state.scale.x),
+                y_scale.inverse((pos.y - state.translate.y) / state.scale.y),
+            )
+        };
+        let to_series_pixels = |point: Point2d| {
+            to_pixels(glm::vec2(
+                x_scale.forward(point.x) * state.scale.x + state.translate.x,
+                y_scale.forward(point.y) * state.scale.y + state.translate.y,
+            ))
+        };
+        let target = to_data(state.mouse_pos);
+        let mut best: Option<(f32, &TooltipSeries, usize, glm::Vec2)> = None;
+        for entry in &self.series {
+            if entry.visible.as_ref().map_or(false, |x| !x.get()) {
+                continue;
+            }
+            let series = entry.series.borrow();
+            let max_distance = self.config.max_distance;
+            if let Some(i) = nearest(&*series, target, max_distance, to_series_pixels) {
+                let pixels = to_series_pixels(series.point(i));
+                let distance = glm::distance(&pixels, &mouse);
+                if best.map_or(true, |(best, ..)| distance < best) {
+                    best = Some((distance, entry, i, pixels));
+                }
+            }
+        }
+        let (_, entry, i, pixels) = match best {
+            Some(x) => x,
+            None => return,
+        };
+        let series = entry.series.borrow();
+        let point = series.point(i);
+        let format = |scale: &Option<Rc<RefCell<Scale>>>, value: f32| match scale {
+            Some(scale) => scale.borrow().convert_to_text(value),
+            None => value.to_string(),
+        };
+        let mut lines = vec![
+            entry.name.clone(),
+            format!("x: {}", format(&self.x_scale, point.x)),
+        ];
+        match series.ohlc(i) {
+            Some(ohlc) => {
+                lines.push(format!("O: {}", format(&self.y_scale, ohlc.open)));
+                lines.push(format!("H: {}", format(&self.y_scale, ohlc.high)));
+                lines.push(format!("L: {}", format(&self.y_scale, ohlc.low)));
+                lines.push(format!("C: {}", format(&self.y_scale, ohlc.close)));
+            }
+            None => lines.push(format!("y: {}", format(&self.y_scale, point.y))),
+        }
+        self.hit = Some(Hit {
+            point: Pos2::new(pixels.x, pixels.y),
+            lines,
+        });
+    }
+}
+
+impl Component for Tooltip {
+    fn on_mouse_moved(&mut self, state: &ChartState) {
+        self.update(state);
+    }
+
+    fn on_zoom(&mut self, state: &ChartState) {
+        self.update(state);
+    }
+
+    fn on_pan(&mut self, state: &ChartState) {
+        self.update(state);
+    }
+
+    fn draw_ui(&mut self, combo: &ChartCombo, drawer: &mut UiPipeline) {
+        let hit = match &self.hit {
+            Some(x) => x,
+            None => return,
+        };
+        let config = &self.config;
+        drawer.circle(
+            hit.point,
+            config.marker_radius,
+            Color32::TRANSPARENT,
+            Stroke::new(1.5, config.text_color),
+        );
+        let sizes: Vec<_> = hit
+            .lines
+            .iter()
+            .map(|x| drawer.text_size(config.text_style, x.as_str()))
+            .collect();
+        let width = sizes.iter().map(|x| x.x).fold(0., f32::max);
+        let height: f32 = sizes.iter().map(|x| x.y).sum();
+        let size = Vec2::new(width, height) + Vec2::splat(config.padding * 2.);
+        // right and below the cursor, flipped to the other side at window edges and clamped if
+        // the box is larger than the window
+        let window = combo.component_rect(combo.node);
+        let mut min = self.mouse + Vec2::splat(config.offset);
+        if min.x + size.x > window.max.x {
+            min.x = self.mouse.x - config.offset - size.x;
+        }
+        if min.y + size.y > window.max.y {
+            min.y = self.mouse.y - config.offset - size.y;
+        }
+        min.x = min.x.min(window.max.x - size.x).max(window.min.x);
+        min.y = min.y.min(window.max.y - size.y).max(window.min.y);
+        let rect = Rect::from_min_size(min, size);
+        drawer.rect(rect, 2., config.fill_color, config.border);
+        let mut pos = rect.min + Vec2::splat(config.padding);
+        for (line, size) in hit.lines.iter().zip(&sizes) {
+            drawer.text_single_line(config.text_style, line.as_str(), pos, config.text_color);
+            pos.y += size.y;
+        }
+    }
+}
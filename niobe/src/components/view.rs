@@ -1,9 +1,10 @@
 use crate::combo::ChartCombo;
 use crate::components::{ChartState, Component, ComponentBase};
 use crate::series::DrawControlFlow;
+use nalgebra_glm::Vec2;
 use niobe_core::buffer::Buffer;
 use niobe_core::pipelines::line::{LineBindGroup, LineDrawer, LineStripPipeline, LineUniform};
-use niobe_core::pipelines::Drawer;
+use niobe_core::pipelines::{BlendMode, Drawer};
 use niobe_core::Point2d;
 use stretch::node::Node;
 use wgpu::util::RenderEncoder;
@@ -18,21 +19,33 @@ impl View {
         &'s mut self,
         combo: &ChartCombo,
         drawer: &mut Drawer<'e, LineDrawer<'s, 'e, &dyn RenderEncoder, LineStripPipeline>>,
+        blend: BlendMode,
     ) -> DrawControlFlow {
         self.base.set_scissor_rect(combo, drawer.pass);
         let mut control_flow = 0u8;
         for series in &mut self.series {
-            control_flow |= series.draw_line_strip(combo, drawer) as u8;
+            if series.blend() == blend {
+                control_flow |= series.draw_line_strip(combo, drawer) as u8;
+            }
         }
         unsafe { std::mem::transmute(control_flow) }
     }
+
+    pub fn node(&self) -> Node {
+        self.base.node
+    }
+
+    /// Sets data to screen space transform of series, eg. after `ChartCombo::zoom_to`.
+    pub fn set_transform(&mut self, scale: Vec2, translate: Vec2) {
+        self.line_ubd[0].scale = scale;
+        self.line_ubd[0].translate = translate;
+    }
 }
 
 impl Component for View {
     fn on_mouse_moved(&mut self, state: &ChartState) {}
 
     fn on_zoom(&mut self, state: &ChartState) {
-        self.line_ubd[0].line_scale *= 2. - delta;
         self.line_ubd[0].scale *= delta
     }
 
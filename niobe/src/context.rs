@@ -3,9 +3,11 @@ use crate::components::view::DrawControlFlow;
 use crate::components::Component;
 use epaint::emath::{Pos2, Rect, Vec2};
 use epaint::Color32;
+use niobe_core::colors::color32_to_wgpu;
 use niobe_core::pipelines::line::{LineShader, LineStripPipeline};
 use niobe_core::pipelines::ui::UiRenderPass;
-use niobe_core::pipelines::{Drawer, PipelineKind};
+use niobe_core::pipelines::{BlendCache, BlendMode, Drawer, MultisampleTarget, PipelineKind};
+use std::iter;
 use std::sync::Arc;
 use wgpu::{Color, Device, LoadOp, Queue, RenderPass, RenderPipeline, TextureFormat, TextureView};
 use winit::dpi::PhysicalSize;
@@ -29,7 +31,13 @@ pub struct DeviceContext {
 
 pub struct WindowContext {
     ui_render_pass: UiRenderPass,
-    line_strip_pipeline: LineStripPipeline,
+    line_shader: LineShader,
+    format: TextureFormat,
+    sample_count: u32,
+    /// series are drawn with the pipeline of their blend mode, see `add_blend_mode`
+    line_strip_pipelines: BlendCache<LineStripPipeline>,
+    /// `None` if anti-aliasing is disabled
+    msaa: Option<MultisampleTarget>,
     components: Vec<Arc<dyn Component>>,
     combos: Vec<ChartCombo>,
     clear_color: Option<wgpu::Color>,
@@ -46,72 +54,51 @@ impl WindowContext {
         window_size: PhysicalSize<u32>,
         scale_factor: f32,
         clear_color: Option<Color32>,
+        sample_count: u32,
     ) -> Self {
-        let clear_color = clear_color.map(|x| wgpu::Color {
-            r: x.r() as f64 / 255.,
-            g: x.g() as f64 / 255.,
-            b: x.b() as f64 / 255.,
-            a: x.a() as f64 / 255.,
-        });
-        let line_shader = LineShader::new(&device);
-        Self {
+        let clear_color = clear_color.map(color32_to_wgpu);
+        let size = window.inner_size();
+        let mut context = Self {
             ui_render_pass: UiRenderPass::new(device, format),
-            line_strip_pipeline: LineStripPipeline::new(&device, format, &line_shader),
+            line_shader: LineShader::new(&device),
+            format,
+            sample_count,
+            line_strip_pipelines: BlendCache::new(),
+            msaa: multisample_target(
+                device,
+                format,
+                sample_count,
+                (size.width, size.height),
+                clear_color,
+            ),
             combos: vec![ChartCombo::new()],
             clear_color,
             window_size: window.inner_size(),
             scale_factor: window.scale_factor() as f32,
             window_id: window.id(),
-        }
+        };
+        context.add_blend_mode(device, BlendMode::default());
+        context
     }
 
-    pub fn draw(&self, device: &Device, queue: &Queue, view: &TextureView) {
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("niobe render encoder"),
+    /// Creates pipelines for series with `blend`, the default blend mode is always available.
+    pub fn add_blend_mode(&mut self, device: &Device, blend: BlendMode) {
+        let (format, sample_count) = (self.format, self.sample_count);
+        let line_shader = &self.line_shader;
+        self.line_strip_pipelines.get_or_create(blend, |blend| {
+            LineStripPipeline::new(device, format, sample_count, blend, line_shader)
         });
-        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-            label: Some("niobe render pass"),
-            color_attachments: &[wgpu::RenderPassColorAttachment {
-                view: &view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: match self.clear_color {
-                        None => LoadOp::Load,
-                        Some(color) => LoadOp::Clear(color),
-                    },
-                    store: true,
-                },
-            }],
-            depth_stencil_attachment: None,
-        });
-        let mut drawer = Drawer {
-            pipeline: &self.line_strip_pipeline,
-            queue,
-            pass: &render_pass,
-        };
-        loop {
-            let mut control_flow = 0u8;
-            drawer.pipeline = &self.line_strip_pipeline;
-            for combo in &self.combos {
-                control_flow |= combo.draw(&mut drawer) as u8;
-            }
-            debug_assert!(control_flow <= DrawControlFlow::DrawRequested as u8);
-            let control_flow = unsafe { std::mem::transmute(control_flow) };
-            if let DrawControlFlow::Finished = control_flow {
-                break;
-            }
-        }
-        let mut drawer = self.ui_render_pass.drawer(Rect::from_min_size(
-            Pos2::ZERO,
-            Vec2::new(self.window_size.0 as f32, self.window_size.1 as f32),
-        ));
-        for combo in &self.combos {
-            combo.draw_ui(&mut drawer);
-        }
-        self.ui_render_pass.render(
+    }
+
+    pub fn draw(&self, device: &Device, queue: &Queue, view: &TextureView) {
+        draw_combos(
             device,
             queue,
             view,
+            self.msaa.as_ref(),
+            &self.line_strip_pipelines,
+            &self.ui_render_pass,
+            &self.combos,
             (self.window_size.width, self.window_size.height),
             self.scale_factor,
             self.clear_color,
@@ -133,6 +120,7 @@ impl WindowContext {
                 match event {
                     WindowEvent::Resized(physical_size) => {
                         self.window_size = *physical_size;
+                        self.resize_msaa(device);
                         return;
                     }
                     WindowEvent::ScaleFactorChanged {
@@ -141,6 +129,7 @@ impl WindowContext {
                     } => {
                         self.scale_factor = scale_factor as f32;
                         self.window_size = **new_inner_size;
+                        self.resize_msaa(device);
                         return;
                     }
                     _ => {}
@@ -167,4 +156,107 @@ impl WindowContext {
             _ => {}
         }
     }
+
+    fn resize_msaa(&mut self, device: &Device) {
+        let size = (self.window_size.width, self.window_size.height);
+        if let Some(msaa) = &mut self.msaa {
+            msaa.resize(device, size);
+        }
+    }
+}
+
+/// Anti-aliasing is disabled if `sample_count` is 1.
+/// Anti-aliasing requires `clear_color`, see `MultisampleTarget::color_attachment`.
+pub(crate) fn multisample_target(
+    device: &Device,
+    format: TextureFormat,
+    sample_count: u32,
+    size: (u32, u32),
+    clear_color: Option<wgpu::Color>,
+) -> Option<MultisampleTarget> {
+    if sample_count > 1 {
+        assert!(
+            clear_color.is_some(),
+            "anti-aliasing requires a clear color"
+        );
+        Some(MultisampleTarget::new(device, format, sample_count, size))
+    } else {
+        None
+    }
+}
+
+/// Records the line strip and ui passes of all `combos` into `view`.
+/// Series are drawn into `msaa` and resolved into `view` if anti-aliasing is enabled, ui is always
+/// drawn directly into `view`. Series pass clears and is submitted first, ui is drawn on top.
+/// Series are drawn in groups by blend mode in the order of `BlendMode::ALL`.
+pub(crate) fn draw_combos(
+    device: &Device,
+    queue: &Queue,
+    view: &TextureView,
+    msaa: Option<&MultisampleTarget>,
+    line_strip_pipelines: &BlendCache<LineStripPipeline>,
+    ui_render_pass: &UiRenderPass,
+    combos: &[ChartCombo],
+    size: (u32, u32),
+    scale_factor: f32,
+    clear_color: Option<wgpu::Color>,
+) {
+    let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+        label: Some("niobe render encoder"),
+    });
+    let color_attachment = match msaa {
+        Some(msaa) => {
+            let clear_color = clear_color.expect("anti-aliasing requires a clear color");
+            msaa.color_attachment(view, clear_color)
+        }
+        None => wgpu::RenderPassColorAttachment {
+            view: &view,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: match clear_color {
+                    None => LoadOp::Load,
+                    Some(color) => LoadOp::Clear(color),
+                },
+                store: true,
+            },
+        },
+    };
+    {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("niobe render pass"),
+            color_attachments: &[color_attachment],
+            depth_stencil_attachment: None,
+        });
+        for (blend, pipeline) in line_strip_pipelines.iter() {
+            let mut drawer = Drawer {
+                pipeline,
+                queue,
+                pass: &mut render_pass,
+            };
+            loop {
+                let mut control_flow = 0u8;
+                drawer.pipeline = pipeline;
+                for combo in combos {
+                    control_flow |= combo.draw(&mut drawer, blend) as u8;
+                }
+                debug_assert!(control_flow <= DrawControlFlow::DrawRequested as u8);
+                let control_flow = unsafe { std::mem::transmute(control_flow) };
+                if let DrawControlFlow::Finished = control_flow {
+                    break;
+                }
+            }
+        }
+    }
+    // series have to be submitted before ui which is rendered by its own encoder
+    queue.submit(iter::once(encoder.finish()));
+    let mut drawer = ui_render_pass.drawer(Rect::from_min_size(
+        Pos2::ZERO,
+        Vec2::new(size.0 as f32, size.1 as f32),
+    ));
+    for combo in combos {
+        combo.draw_ui(&mut drawer);
+    }
+    // series pass that was submitted above always runs, even without series, so it has already
+    // cleared `view` and resolved `msaa` into it, ui only loads it
+    ui_render_pass.render(device, queue, view, size, scale_factor, None);
 }
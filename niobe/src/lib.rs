@@ -7,6 +7,7 @@ pub mod chart;
 pub mod combo;
 pub mod components;
 pub mod context;
+pub mod offscreen;
 pub mod series;
 use nalgebra_glm::Vec2;
 
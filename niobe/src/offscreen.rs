@@ -0,0 +1,274 @@
+use crate::combo::ChartCombo;
+use crate::context::{draw_combos, multisample_target};
+use epaint::Color32;
+use image::{ImageError, RgbaImage};
+use niobe_core::colors::color32_to_wgpu;
+use niobe_core::pipelines::line::{LineShader, LineStripPipeline};
+use niobe_core::pipelines::ui::UiRenderPass;
+use niobe_core::pipelines::{BlendCache, BlendMode, MultisampleTarget};
+use std::fmt;
+use std::iter;
+use std::num::NonZeroU32;
+use std::path::Path;
+use wgpu::{
+    BufferAsyncError, BufferUsages, Device, Extent3d, Maintain, MapMode, Queue, Texture,
+    TextureFormat, TextureUsages, TextureView,
+};
+
+/// Format of the offscreen texture, pixels are copied out as is so it needs to match
+/// `image::Rgba<u8>`.
+pub const OFFSCREEN_FORMAT: TextureFormat = TextureFormat::Rgba8UnormSrgb;
+
+/// Renders charts into a texture instead of a window surface.
+/// Useful for server side rendering and golden image tests where there is no window available.
+pub struct OffscreenContext {
+    ui_render_pass: UiRenderPass,
+    line_shader: LineShader,
+    sample_count: u32,
+    /// series are drawn with the pipeline of their blend mode, see `add_blend_mode`
+    line_strip_pipelines: BlendCache<LineStripPipeline>,
+    /// `None` if anti-aliasing is disabled
+    msaa: Option<MultisampleTarget>,
+    combos: Vec<ChartCombo>,
+    clear_color: Option<wgpu::Color>,
+    target: OffscreenTarget,
+    scale_factor: f32,
+}
+
+impl OffscreenContext {
+    pub fn new(
+        device: &Device,
+        width: u32,
+        height: u32,
+        scale_factor: f32,
+        clear_color: Option<Color32>,
+        sample_count: u32,
+    ) -> Self {
+        let clear_color = clear_color.map(color32_to_wgpu);
+        let mut context = Self {
+            ui_render_pass: UiRenderPass::new(device, OFFSCREEN_FORMAT),
+            line_shader: LineShader::new(&device),
+            sample_count,
+            line_strip_pipelines: BlendCache::new(),
+            msaa: multisample_target(
+                device,
+                OFFSCREEN_FORMAT,
+                sample_count,
+                (width, height),
+                clear_color,
+            ),
+            combos: vec![ChartCombo::new()],
+            clear_color,
+            target: OffscreenTarget::new(device, width, height),
+            scale_factor,
+        };
+        context.add_blend_mode(device, BlendMode::default());
+        context
+    }
+
+    /// Creates pipelines for series with `blend`, the default blend mode is always available.
+    pub fn add_blend_mode(&mut self, device: &Device, blend: BlendMode) {
+        let sample_count = self.sample_count;
+        let line_shader = &self.line_shader;
+        self.line_strip_pipelines.get_or_create(blend, |blend| {
+            LineStripPipeline::new(device, OFFSCREEN_FORMAT, sample_count, blend, line_shader)
+        });
+    }
+
+    pub fn combos_mut(&mut self) -> &mut Vec<ChartCombo> {
+        &mut self.combos
+    }
+
+    /// Recreates the target texture if the size has changed.
+    pub fn resize(&mut self, device: &Device, width: u32, height: u32, scale_factor: f32) {
+        self.scale_factor = scale_factor;
+        if self.target.width != width || self.target.height != height {
+            self.target = OffscreenTarget::new(device, width, height);
+        }
+        if let Some(msaa) = &mut self.msaa {
+            msaa.resize(device, (width, height));
+        }
+    }
+
+    pub fn draw(&self, device: &Device, queue: &Queue) {
+        draw_combos(
+            device,
+            queue,
+            &self.target.view,
+            self.msaa.as_ref(),
+            &self.line_strip_pipelines,
+            &self.ui_render_pass,
+            &self.combos,
+            (self.target.width, self.target.height),
+            self.scale_factor,
+            self.clear_color,
+        );
+    }
+
+    /// Draws all combos and reads the result back.
+    pub async fn render_image(
+        &self,
+        device: &Device,
+        queue: &Queue,
+    ) -> Result<RgbaImage, BufferAsyncError> {
+        self.draw(device, queue);
+        self.target.read_image(device, queue).await
+    }
+
+    pub async fn render_png(
+        &self,
+        device: &Device,
+        queue: &Queue,
+        path: impl AsRef<Path>,
+    ) -> Result<(), RenderPngError> {
+        Ok(self.render_image(device, queue).await?.save(path)?)
+    }
+
+    pub fn target(&self) -> &OffscreenTarget {
+        &self.target
+    }
+}
+
+/// Error of `OffscreenContext::render_png`.
+#[derive(Debug)]
+pub enum RenderPngError {
+    /// rendered texture couldn't be read back
+    Readback(BufferAsyncError),
+    /// image couldn't be encoded or written
+    Image(ImageError),
+}
+
+impl fmt::Display for RenderPngError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Readback(e) => write!(f, "failed to read back rendered image: {}", e),
+            Self::Image(e) => write!(f, "failed to save image: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RenderPngError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Readback(e) => Some(e),
+            Self::Image(e) => Some(e),
+        }
+    }
+}
+
+impl From<BufferAsyncError> for RenderPngError {
+    fn from(e: BufferAsyncError) -> Self {
+        Self::Readback(e)
+    }
+}
+
+impl From<ImageError> for RenderPngError {
+    fn from(e: ImageError) -> Self {
+        Self::Image(e)
+    }
+}
+
+/// A texture that can be rendered into and copied back to the cpu.
+pub struct OffscreenTarget {
+    texture: Texture,
+    view: TextureView,
+    readback: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+}
+
+impl OffscreenTarget {
+    pub fn new(device: &Device, width: u32, height: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("niobe offscreen target"),
+            size: Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: OFFSCREEN_FORMAT,
+            usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // rows copied into a buffer must be aligned
+        let unpadded_bytes_per_row = width * 4;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (unpadded_bytes_per_row + align - 1) / align * align;
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("niobe offscreen readback"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        Self {
+            texture,
+            view,
+            readback,
+            width,
+            height,
+            padded_bytes_per_row,
+        }
+    }
+
+    pub fn view(&self) -> &TextureView {
+        &self.view
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Copies the texture into an image, everything that was submitted before is included.
+    pub async fn read_image(
+        &self,
+        device: &Device,
+        queue: &Queue,
+    ) -> Result<RgbaImage, BufferAsyncError> {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("niobe offscreen readback encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &self.readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(self.padded_bytes_per_row),
+                    rows_per_image: NonZeroU32::new(self.height),
+                },
+            },
+            Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+        queue.submit(iter::once(encoder.finish()));
+
+        let slice = self.readback.slice(..);
+        let mapping = slice.map_async(MapMode::Read);
+        // mapping is only resolved when the device is polled
+        device.poll(Maintain::Wait);
+        mapping.await?;
+        let unpadded_bytes_per_row = self.width as usize * 4;
+        let mut pixels = Vec::with_capacity(unpadded_bytes_per_row * self.height as usize);
+        {
+            let map = slice.get_mapped_range();
+            for row in map.chunks(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..unpadded_bytes_per_row]);
+            }
+        }
+        self.readback.unmap();
+        Ok(RgbaImage::from_raw(self.width, self.height, pixels)
+            .expect("readback buffer matches image size"))
+    }
+}
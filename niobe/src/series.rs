@@ -3,7 +3,7 @@ use crate::components::scale::Orientation;
 use crate::components::{Component, PipelineKind};
 use epaint::emath::Rect;
 use nalgebra_glm::Vec2;
-use niobe_core::buffer::Buffer;
+use niobe_core::buffer::RawBuffer;
 use niobe_core::pipelines::line::{LineDrawer, LineStripPipeline};
 use niobe_core::pipelines::ui::UiRenderPass;
 use niobe_core::pipelines::Drawer;
@@ -13,7 +13,12 @@ use std::sync::Arc;
 use wgpu::util::RenderEncoder;
 use wgpu::{BindGroup, IndexFormat, RenderPass};
 
+pub mod area;
+pub mod bar;
 pub mod line;
+pub mod nearest;
+pub mod ohlc;
+pub mod scatter;
 
 #[repr(u8)]
 pub enum DrawControlFlow {
@@ -25,8 +30,8 @@ pub struct Series {
     instance_range: Range<u32>,
     vertex_range: Range<u32>,
     bind_group: Arc<BindGroup>,
-    buffers: HashMap<u32, Arc<Buffer>>,
-    index: Option<(Arc<Buffer>, IndexFormat)>,
+    buffers: HashMap<u32, Arc<RawBuffer>>,
+    index: Option<(Arc<RawBuffer>, IndexFormat)>,
     pipeline_kind: PipelineKind,
 }
 
@@ -39,11 +44,11 @@ impl Series {
         self.bind_group = bind_group;
     }
 
-    pub fn set_buffer(&mut self, id: u32, buffer: Arc<Buffer>) {
+    pub fn set_buffer(&mut self, id: u32, buffer: Arc<RawBuffer>) {
         self.buffers.insert(id, buffer);
     }
 
-    pub fn set_index_buffer(&mut self, buffer: Arc<Buffer>, format: IndexFormat) {
+    pub fn set_index_buffer(&mut self, buffer: Arc<RawBuffer>, format: IndexFormat) {
         self.index = Some((buffer, format));
     }
 }
@@ -0,0 +1,265 @@
+use nalgebra_glm::Vec2;
+use niobe_core::buffer::{Buffer, BufferSlice};
+use niobe_core::dash::DashDistances;
+use niobe_core::pipelines::area::{AreaBindGroup, AreaDrawer, AreaUniform};
+use niobe_core::pipelines::line::{
+    LineBindGroup, LineCap, LineDrawer, LineStripPipeline, LineStyle, LineUniform,
+};
+use niobe_core::pipelines::{AxisScale, BlendMode};
+use niobe_core::Point2d;
+use rgb::RGBA;
+use std::cell::Cell;
+use std::rc::Rc;
+use wgpu::util::RenderEncoder;
+use wgpu::{BufferUsages, Device, Queue};
+
+pub enum AreaFill {
+    /// Fill between the line and a constant value.
+    Baseline(f32),
+    /// Fill between the line and a second line with the same number of points,
+    /// eg. bands of bollinger bands or a confidence interval.
+    Between,
+}
+
+pub struct AreaSeriesConfig {
+    pub fill: AreaFill,
+    /// alpha is used for blending
+    pub fill_color: RGBA<f32>,
+    /// line drawn on top of the upper line and the lower one when filling between lines, not
+    /// drawn if `None`
+    pub stroke_color: Option<RGBA<f32>>,
+    pub stroke_width: u32,
+    pub stroke_style: LineStyle,
+    /// blend mode of the fill and stroke pipelines, see `BlendCache`
+    pub blend: BlendMode,
+}
+
+/// Filled area under a line or between two lines.
+/// NaN values in either line create gaps in the fill.
+pub struct AreaSeries {
+    /// upper line kept on the cpu for lookups under the cursor
+    data: Vec<Point2d>,
+    visible: Rc<Cell<bool>>,
+    blend: BlendMode,
+    upper: Buffer<Point2d>,
+    lower: Option<Buffer<Point2d>>,
+    /// lower line on the cpu for measuring dashes, empty with a baseline
+    lower_data: Vec<Point2d>,
+    stroke: bool,
+    stroke_width: u32,
+    stroke_cap: LineCap,
+    /// `None` for solid strokes
+    dashes: Option<DashDistances>,
+    /// `None` for solid strokes or with a baseline
+    lower_dashes: Option<DashDistances>,
+    x_scale: AxisScale,
+    y_scale: AxisScale,
+    pixels_per_unit: Vec2,
+    area_ubd: [AreaUniform; 1],
+    area_ubo: Buffer<AreaUniform>,
+    area_group: AreaBindGroup,
+    line_ubd: [LineUniform; 1],
+    line_ubo: Buffer<LineUniform>,
+    line_group: LineBindGroup,
+}
+
+impl AreaSeries {
+    pub fn new(device: &Device, config: &AreaSeriesConfig) -> Self {
+        let dashed = config.stroke_style.dash.is_some();
+        let (baseline, lower) = match config.fill {
+            AreaFill::Baseline(baseline) => (Some(baseline), None),
+            AreaFill::Between => (
+                None,
+                Some(Buffer::with_capacity(device, BufferUsages::VERTEX, 1024)),
+            ),
+        };
+        let area_ubd = [AreaUniform {
+            color: config.fill_color,
+            scale: Vec2::new(1., 1.),
+            translate: Vec2::new(0., 0.),
+            baseline: baseline.unwrap_or(0.),
+            use_baseline: baseline.is_some() as u32,
+            log_base: Vec2::new(0., 0.),
+        }];
+        let area_ubo = Buffer::new(
+            &device,
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            &area_ubd,
+        );
+        let line_ubd = [LineUniform::new(
+            config.stroke_color.unwrap_or(config.fill_color),
+            &config.stroke_style,
+        )];
+        let line_ubo = Buffer::new(
+            &device,
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            &line_ubd,
+        );
+        Self {
+            data: Vec::new(),
+            visible: Rc::new(Cell::new(true)),
+            blend: config.blend,
+            upper: Buffer::with_capacity(device, BufferUsages::VERTEX, 1024),
+            lower_dashes: (dashed && lower.is_some()).then(|| DashDistances::new(device)),
+            lower,
+            lower_data: Vec::new(),
+            stroke: config.stroke_color.is_some(),
+            stroke_width: config.stroke_width,
+            stroke_cap: config.stroke_style.cap,
+            dashes: dashed.then(|| DashDistances::new(device)),
+            x_scale: AxisScale::Linear,
+            y_scale: AxisScale::Linear,
+            pixels_per_unit: Vec2::new(1., 1.),
+            area_group: AreaBindGroup::new(&device, &area_ubo.slice(..)),
+            area_ubd,
+            area_ubo,
+            line_group: LineBindGroup::new(&device, &line_ubo.slice(..)),
+            line_ubd,
+            line_ubo,
+        }
+    }
+
+    /// Blend mode of the pipeline this series must be drawn with.
+    pub fn blend(&self) -> BlendMode {
+        self.blend
+    }
+
+    /// Series is not drawn while the flag is false, eg. it's toggled by `Legend`.
+    pub fn visibility(&self) -> Rc<Cell<bool>> {
+        self.visible.clone()
+    }
+
+    /// Points of the upper line.
+    pub fn data(&self) -> &[Point2d] {
+        &self.data
+    }
+
+    /// Appends points, `lower` is required when filling between two lines and nothing is
+    /// appended without it. It's ignored with a baseline.
+    pub fn extend(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        upper: &[Point2d],
+        lower: Option<&[Point2d]>,
+    ) {
+        if let Some(buffer) = &mut self.lower {
+            let lower = match lower {
+                Some(x) => x,
+                None => return,
+            };
+            debug_assert_eq!(upper.len(), lower.len());
+            buffer.extend(device, queue, lower);
+            self.lower_data.extend_from_slice(lower);
+        }
+        self.upper.extend(device, queue, upper);
+        self.data.extend_from_slice(upper);
+        self.update_dashes(device, queue);
+    }
+
+    pub fn clear(&mut self) {
+        self.upper.clear();
+        self.data.clear();
+        self.lower_data.clear();
+        if let Some(lower) = &mut self.lower {
+            lower.clear();
+        }
+        for dashes in self.dashes.iter_mut().chain(&mut self.lower_dashes) {
+            dashes.invalidate();
+        }
+    }
+
+    pub fn set_baseline(&mut self, queue: &Queue, baseline: f32) {
+        self.area_ubd[0].baseline = baseline;
+        self.area_ubo.write_sliced(queue, .., &self.area_ubd);
+    }
+
+    /// Log axes transform points in the shader, takes effect on the next `set_transform`.
+    pub fn set_axis_scales(&mut self, x: AxisScale, y: AxisScale) {
+        self.area_ubd[0].log_base = AxisScale::log_bases(x, y);
+        self.line_ubd[0].log_base = AxisScale::log_bases(x, y);
+        self.x_scale = x;
+        self.y_scale = y;
+    }
+
+    /// Updates data to clip space transform, `pixel_scale` is size of a pixel in clip space.
+    pub fn set_transform(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scale: Vec2,
+        translate: Vec2,
+        pixel_scale: Vec2,
+    ) {
+        self.area_ubd[0].scale = scale;
+        self.area_ubd[0].translate = translate;
+        self.line_ubd[0].scale = scale;
+        self.line_ubd[0].translate = translate;
+        self.line_ubd[0].line_scale = pixel_scale * self.stroke_width as f32;
+        self.area_ubo.write_sliced(queue, .., &self.area_ubd);
+        self.line_ubo.write_sliced(queue, .., &self.line_ubd);
+        self.pixels_per_unit = scale.component_div(&pixel_scale);
+        self.update_dashes(device, queue);
+    }
+
+    fn update_dashes(&mut self, device: &Device, queue: &Queue) {
+        let (x_scale, y_scale) = (self.x_scale, self.y_scale);
+        let pixels_per_unit = self.pixels_per_unit;
+        let mut update = |dashes: &mut Option<DashDistances>, points: &[Point2d]| {
+            if let Some(dashes) = dashes {
+                dashes.update::<LineStripPipeline>(
+                    device,
+                    queue,
+                    points,
+                    x_scale,
+                    y_scale,
+                    pixels_per_unit,
+                );
+            }
+        };
+        update(&mut self.dashes, &self.data);
+        update(&mut self.lower_dashes, &self.lower_data);
+    }
+
+    pub fn draw_area<'s, 'e, E: RenderEncoder<'s>>(&'s self, drawer: &mut AreaDrawer<'s, 'e, E>) {
+        if !self.visible.get() {
+            return;
+        }
+        drawer.set_bind_group(&self.area_group, 0).draw(
+            self.upper.slice(..),
+            self.lower.as_ref().map(|x| x.slice(..)),
+        );
+    }
+
+    /// Draws stroke, needs to be called after `draw_area` to be on top.
+    pub fn draw_line_strip<'s, 'e, E: RenderEncoder<'s>>(
+        &'s self,
+        drawer: &mut LineDrawer<'s, 'e, E, LineStripPipeline>,
+    ) {
+        if !self.visible.get() || !self.stroke {
+            return;
+        }
+        drawer
+            .set_bind_group(&self.line_group, 0)
+            .set_cap(self.stroke_cap);
+        draw_stroke(drawer, self.upper.slice(..), &self.dashes);
+        if let Some(lower) = &self.lower {
+            draw_stroke(drawer, lower.slice(..), &self.lower_dashes);
+        }
+    }
+}
+
+/// Draws one line of the stroke, dashed if it has distances.
+fn draw_stroke<'s, 'e, E: RenderEncoder<'s>>(
+    drawer: &mut LineDrawer<'s, 'e, E, LineStripPipeline>,
+    points: BufferSlice<'s, Point2d>,
+    dashes: &'s Option<DashDistances>,
+) {
+    if points.len() < 2 {
+        return;
+    }
+    match dashes {
+        Some(dashes) => drawer.draw_dashed(points, dashes.slice()),
+        None => drawer.draw(points),
+    };
+}
@@ -0,0 +1,236 @@
+use crate::components::scale::Orientation;
+use nalgebra_glm::Vec2;
+use niobe_core::buffer::Buffer;
+use niobe_core::pipelines::mesh::{MeshBindGroup, MeshDrawer, MeshUniform, RectInstance};
+use niobe_core::pipelines::{AxisScale, BlendMode};
+use niobe_core::{Mesh2d, Point2d};
+use rgb::RGBA;
+use std::cell::Cell;
+use std::rc::Rc;
+use wgpu::util::RenderEncoder;
+use wgpu::{BufferUsages, Device, Queue};
+
+const POSITIVE: usize = 0;
+const NEGATIVE: usize = 1;
+
+#[derive(Copy, Clone, Debug)]
+pub enum BarWidth {
+    /// Width scales with zoom.
+    Data(f32),
+    /// Width stays the same when zooming.
+    Pixels(u32),
+}
+
+/// Position of a series among other bar series that share the same x values.
+#[derive(Copy, Clone, Debug)]
+pub struct BarGroup {
+    pub index: u32,
+    pub count: u32,
+    /// space between bars of the same group, in the same units as bar width
+    pub gap: f32,
+}
+
+impl Default for BarGroup {
+    fn default() -> Self {
+        Self {
+            index: 0,
+            count: 1,
+            gap: 0.,
+        }
+    }
+}
+
+impl BarGroup {
+    /// Offset of bar center from the x value in units of bar width.
+    fn offset(&self, width: f32) -> f32 {
+        (self.index as f32 - (self.count as f32 - 1.) / 2.) * (width + self.gap)
+    }
+}
+
+pub struct BarSeriesConfig {
+    /// `Vertical` bars grow along y axis, `Horizontal` ones along x axis.
+    pub orientation: Orientation,
+    pub color: RGBA<f32>,
+    /// color of bars below baseline, same as `color` if not set
+    pub negative_color: Option<RGBA<f32>>,
+    pub baseline: f32,
+    pub width: BarWidth,
+    pub group: BarGroup,
+    /// blend mode of the pipeline the series is drawn with, see `BlendCache`
+    pub blend: BlendMode,
+}
+
+/// Bar or histogram series, each bar is an instance of a quad that spans from baseline to the
+/// value.
+/// Points are (position, value), for vertical bars position is x and value is y.
+pub struct BarSeries {
+    orientation: Orientation,
+    baseline: f32,
+    width: BarWidth,
+    group: BarGroup,
+    data: Vec<Point2d>,
+    visible: Rc<Cell<bool>>,
+    blend: BlendMode,
+    instances: [Buffer<RectInstance>; 2],
+    quad: Mesh2d<u16>,
+    mesh_ubd: [MeshUniform; 2],
+    mesh_ubo: Buffer<MeshUniform>,
+    mesh_group: MeshBindGroup,
+}
+
+impl BarSeries {
+    pub fn new(device: &Device, config: &BarSeriesConfig) -> Self {
+        let uniform = |color| MeshUniform {
+            color,
+            scale: Vec2::new(1., 1.),
+            translate: Vec2::new(0., 0.),
+            mesh_scale: Vec2::new(0., 0.),
+            log_base: Vec2::new(0., 0.),
+        };
+        let mesh_ubd = [
+            uniform(config.color),
+            uniform(config.negative_color.unwrap_or(config.color)),
+        ];
+        let mesh_ubo = Buffer::new(
+            &device,
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            &mesh_ubd,
+        );
+        let mesh_group = MeshBindGroup::new(&device, mesh_ubo.slice(..));
+        let buffer = || Buffer::with_capacity(device, BufferUsages::VERTEX, 1024);
+        Self {
+            orientation: config.orientation,
+            baseline: config.baseline,
+            width: config.width,
+            group: config.group,
+            data: Vec::new(),
+            visible: Rc::new(Cell::new(true)),
+            blend: config.blend,
+            instances: [buffer(), buffer()],
+            quad: Mesh2d::quad(device),
+            mesh_ubd,
+            mesh_ubo,
+            mesh_group,
+        }
+    }
+
+    /// Blend mode of the pipeline this series must be drawn with.
+    pub fn blend(&self) -> BlendMode {
+        self.blend
+    }
+
+    /// Series is not drawn while the flag is false, eg. it's toggled by `Legend`.
+    pub fn visibility(&self) -> Rc<Cell<bool>> {
+        self.visible.clone()
+    }
+
+    pub fn data(&self) -> &[Point2d] {
+        &self.data
+    }
+
+    pub fn extend(&mut self, device: &Device, queue: &Queue, data: &[Point2d]) {
+        let mut rects = [Vec::new(), Vec::new()];
+        for point in data {
+            rects[self.sign(point)].push(self.rect(point));
+        }
+        for (instances, rects) in self.instances.iter_mut().zip(&rects) {
+            if !rects.is_empty() {
+                instances.extend(device, queue, rects);
+            }
+        }
+        self.data.extend_from_slice(data);
+    }
+
+    pub fn push(&mut self, device: &Device, queue: &Queue, point: Point2d) {
+        self.extend(device, queue, &[point]);
+    }
+
+    pub fn set_data(&mut self, device: &Device, queue: &Queue, data: &[Point2d]) {
+        self.data.clear();
+        self.instances.iter_mut().for_each(Buffer::clear);
+        self.extend(device, queue, data);
+    }
+
+    pub fn set_baseline(&mut self, device: &Device, queue: &Queue, baseline: f32) {
+        self.baseline = baseline;
+        let data = std::mem::take(&mut self.data);
+        self.set_data(device, queue, &data);
+    }
+
+    /// Log axes transform points in the shader, takes effect on the next `set_transform`.
+    pub fn set_axis_scales(&mut self, x: AxisScale, y: AxisScale) {
+        for uniform in &mut self.mesh_ubd {
+            uniform.log_base = AxisScale::log_bases(x, y);
+        }
+    }
+
+    /// Updates data to clip space transform, `pixel_scale` is size of a pixel in clip space.
+    pub fn set_transform(
+        &mut self,
+        queue: &Queue,
+        scale: Vec2,
+        translate: Vec2,
+        pixel_scale: Vec2,
+    ) {
+        // offsets in data units are already baked into instances
+        let (mesh_scale, offset) = match self.width {
+            BarWidth::Data(_) => (0., 0.),
+            BarWidth::Pixels(width) => (width as f32, self.group.offset(width as f32)),
+        };
+        for uniform in &mut self.mesh_ubd {
+            uniform.scale = scale;
+            uniform.translate = translate;
+            match self.orientation {
+                Orientation::Vertical => {
+                    uniform.mesh_scale = Vec2::new(mesh_scale * pixel_scale.x, 0.);
+                    uniform.translate.x += offset * pixel_scale.x;
+                }
+                Orientation::Horizontal => {
+                    uniform.mesh_scale = Vec2::new(0., mesh_scale * pixel_scale.y);
+                    uniform.translate.y += offset * pixel_scale.y;
+                }
+            }
+        }
+        self.mesh_ubo.write_sliced(queue, .., &self.mesh_ubd);
+    }
+
+    pub fn draw_mesh<'s, 'e, E: RenderEncoder<'s>>(&'s self, drawer: &mut MeshDrawer<'e, E>) {
+        if !self.visible.get() {
+            return;
+        }
+        drawer
+            .set_vertices(self.quad.vertices.slice(..))
+            .set_indices(self.quad.indices.slice(..))
+            .set_bind_group(&self.mesh_group, POSITIVE as u32)
+            .draw_rects(self.instances[POSITIVE].slice(..))
+            .set_bind_group(&self.mesh_group, NEGATIVE as u32)
+            .draw_rects(self.instances[NEGATIVE].slice(..));
+    }
+
+    fn sign(&self, point: &Point2d) -> usize {
+        if point.y >= self.baseline {
+            POSITIVE
+        } else {
+            NEGATIVE
+        }
+    }
+
+    fn rect(&self, point: &Point2d) -> RectInstance {
+        let (position, width) = match self.width {
+            BarWidth::Data(width) => (point.x + self.group.offset(width), width),
+            BarWidth::Pixels(_) => (point.x, 0.),
+        };
+        let center = (point.y + self.baseline) / 2.;
+        let length = (point.y - self.baseline).abs();
+        match self.orientation {
+            Orientation::Vertical => RectInstance {
+                pos: Point2d::new(position, center),
+                size: Vec2::new(width, length),
+            },
+            Orientation::Horizontal => RectInstance {
+                pos: Point2d::new(center, position),
+                size: Vec2::new(length, width),
+            },
+        }
+    }
+}
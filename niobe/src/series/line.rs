@@ -1,14 +1,191 @@
-use crate::combo::ChartCombo;
-use crate::series::{DrawControlFlow, Series};
+use nalgebra_glm::Vec2;
 use niobe_core::buffer::Buffer;
-use niobe_core::pipelines::line::{LineBindGroup, LineDrawer, LineStripPipeline, LineUniform};
-use niobe_core::pipelines::Drawer;
+use niobe_core::dash::DashDistances;
+use niobe_core::lod::M4Decimator;
+use niobe_core::pipelines::line::{
+    LineBindGroup, LineCap, LineDrawer, LineStripPipeline, LineStyle, LineUniform,
+};
+use niobe_core::pipelines::{AxisScale, BlendMode};
 use niobe_core::Point2d;
+use rgb::RGBA;
+use std::cell::Cell;
+use std::rc::Rc;
 use wgpu::util::RenderEncoder;
+use wgpu::{BufferUsages, Device, Queue};
 
-struct LineSeries {
+pub struct LineSeriesConfig {
+    pub color: RGBA<f32>,
+    pub width: u32,
+    pub style: LineStyle,
+    /// blend mode of the pipeline the series is drawn with, see `BlendCache`
+    pub blend: BlendMode,
+}
+
+/// Line strip through points sorted by x.
+/// Points are also kept on the cpu for lookups under the cursor and for decimating the visible
+/// range, see `M4Decimator`. Lines on a log x axis are not decimated.
+/// Dashed lines measure the points that are drawn, see `DashDistances`.
+pub struct LineSeries {
+    data: Vec<Point2d>,
+    visible: Rc<Cell<bool>>,
+    blend: BlendMode,
+    width: u32,
+    cap: LineCap,
+    x_scale: AxisScale,
+    y_scale: AxisScale,
+    decimator: M4Decimator,
+    /// visible x range and its width in pixels from the last `set_transform`
+    x_range: Option<(f32, f32, u32)>,
+    /// `None` for solid lines
+    dashes: Option<DashDistances>,
+    pixels_per_unit: Vec2,
     line_ubd: [LineUniform; 1],
     line_ubo: Buffer<LineUniform>,
     line_ebo: Buffer<Point2d>,
     line_bind_group: LineBindGroup,
 }
+
+impl LineSeries {
+    pub fn new(device: &Device, config: &LineSeriesConfig) -> Self {
+        let line_ubd = [LineUniform::new(config.color, &config.style)];
+        let line_ubo = Buffer::new(
+            &device,
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            &line_ubd,
+        );
+        Self {
+            data: Vec::new(),
+            visible: Rc::new(Cell::new(true)),
+            blend: config.blend,
+            width: config.width,
+            cap: config.style.cap,
+            x_scale: AxisScale::Linear,
+            y_scale: AxisScale::Linear,
+            decimator: M4Decimator::new(device),
+            x_range: None,
+            dashes: config
+                .style
+                .dash
+                .is_some()
+                .then(|| DashDistances::new(device)),
+            pixels_per_unit: Vec2::new(1., 1.),
+            line_bind_group: LineBindGroup::new(&device, &line_ubo.slice(..)),
+            line_ubd,
+            line_ubo,
+            line_ebo: Buffer::with_capacity(device, BufferUsages::VERTEX, 1024),
+        }
+    }
+
+    /// Blend mode of the pipeline this series must be drawn with.
+    pub fn blend(&self) -> BlendMode {
+        self.blend
+    }
+
+    /// Series is not drawn while the flag is false, eg. it's toggled by `Legend`.
+    pub fn visibility(&self) -> Rc<Cell<bool>> {
+        self.visible.clone()
+    }
+
+    pub fn data(&self) -> &[Point2d] {
+        &self.data
+    }
+
+    /// Appends points, they must not be before the last point.
+    pub fn extend(&mut self, device: &Device, queue: &Queue, data: &[Point2d]) {
+        self.line_ebo.extend(device, queue, data);
+        self.data.extend_from_slice(data);
+        self.decimator.invalidate();
+        self.update(device, queue);
+    }
+
+    pub fn clear(&mut self) {
+        self.line_ebo.clear();
+        self.data.clear();
+        self.decimator.invalidate();
+        if let Some(dashes) = &mut self.dashes {
+            dashes.invalidate();
+        }
+        // nothing to draw until the next `set_transform`
+        self.x_range = None;
+    }
+
+    /// Decimates the visible range and measures dashes of the points that are drawn.
+    fn update(&mut self, device: &Device, queue: &Queue) {
+        let decimated = match self.x_range {
+            Some((x_min, x_max, columns)) => self
+                .decimator
+                .update(device, queue, &self.data, x_min, x_max, columns),
+            None => false,
+        };
+        if let Some(dashes) = &mut self.dashes {
+            if decimated {
+                dashes.invalidate();
+            }
+            let points = match self.x_range {
+                Some(_) => self.decimator.points(),
+                None => &self.data,
+            };
+            dashes.update::<LineStripPipeline>(
+                device,
+                queue,
+                points,
+                self.x_scale,
+                self.y_scale,
+                self.pixels_per_unit,
+            );
+        }
+    }
+
+    /// Log axes transform points in the shader, takes effect on the next `set_transform`.
+    pub fn set_axis_scales(&mut self, x: AxisScale, y: AxisScale) {
+        self.line_ubd[0].log_base = AxisScale::log_bases(x, y);
+        self.x_scale = x;
+        self.y_scale = y;
+    }
+
+    /// Updates data to clip space transform, `pixel_scale` is size of a pixel in clip space.
+    pub fn set_transform(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        scale: Vec2,
+        translate: Vec2,
+        pixel_scale: Vec2,
+    ) {
+        self.line_ubd[0].scale = scale;
+        self.line_ubd[0].translate = translate;
+        self.line_ubd[0].line_scale = pixel_scale * self.width as f32;
+        self.line_ubo.write_sliced(queue, .., &self.line_ubd);
+        self.pixels_per_unit = scale.component_div(&pixel_scale);
+        // clip space -1..1 in data units
+        self.x_range = match self.x_scale {
+            AxisScale::Linear => Some((
+                (-1. - translate.x) / scale.x,
+                (1. - translate.x) / scale.x,
+                (2. / pixel_scale.x).round() as u32,
+            )),
+            AxisScale::Log(_) => None,
+        };
+        self.update(device, queue);
+    }
+
+    pub fn draw_line_strip<'s, 'e, E: RenderEncoder<'s>>(
+        &'s self,
+        drawer: &mut LineDrawer<'s, 'e, E, LineStripPipeline>,
+    ) {
+        let points = match self.x_range {
+            Some(_) => self.decimator.slice(),
+            None => self.line_ebo.slice(..),
+        };
+        if !self.visible.get() || points.len() < 2 {
+            return;
+        }
+        drawer
+            .set_bind_group(&self.line_bind_group, 0)
+            .set_cap(self.cap);
+        match &self.dashes {
+            Some(dashes) => drawer.draw_dashed(points, dashes.slice()),
+            None => drawer.draw(points),
+        };
+    }
+}
@@ -0,0 +1,204 @@
+use crate::series::area::AreaSeries;
+use crate::series::bar::BarSeries;
+use crate::series::line::LineSeries;
+use crate::series::ohlc::{Ohlc, OhlcSeries};
+use crate::series::scatter::ScatterSeries;
+use nalgebra_glm::Vec2;
+use niobe_core::Point2d;
+
+/// Points of a series that are kept on the CPU, used to look up values under the cursor.
+/// Points must be sorted by x.
+pub trait SeriesPoints {
+    fn len(&self) -> usize;
+
+    /// Point that is snapped to, candles use close.
+    fn point(&self, i: usize) -> Point2d;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn last(&self) -> Option<Point2d> {
+        self.len().checked_sub(1).map(|i| self.point(i))
+    }
+
+    /// Candle of candle series.
+    fn ohlc(&self, _i: usize) -> Option<Ohlc> {
+        None
+    }
+}
+
+impl SeriesPoints for [Point2d] {
+    fn len(&self) -> usize {
+        <[Point2d]>::len(self)
+    }
+
+    fn point(&self, i: usize) -> Point2d {
+        self[i]
+    }
+}
+
+impl SeriesPoints for Vec<Point2d> {
+    fn len(&self) -> usize {
+        <[Point2d]>::len(self)
+    }
+
+    fn point(&self, i: usize) -> Point2d {
+        self[i]
+    }
+}
+
+impl SeriesPoints for [Ohlc] {
+    fn len(&self) -> usize {
+        <[Ohlc]>::len(self)
+    }
+
+    fn point(&self, i: usize) -> Point2d {
+        Point2d::new(self[i].x, self[i].close)
+    }
+
+    fn ohlc(&self, i: usize) -> Option<Ohlc> {
+        Some(self[i])
+    }
+}
+
+impl SeriesPoints for LineSeries {
+    fn len(&self) -> usize {
+        self.data().len()
+    }
+
+    fn point(&self, i: usize) -> Point2d {
+        self.data()[i]
+    }
+}
+
+/// Points in the order of `ScatterSeries::by_x` so they can be added in any order.
+impl SeriesPoints for ScatterSeries {
+    fn len(&self) -> usize {
+        self.by_x().len()
+    }
+
+    fn point(&self, i: usize) -> Point2d {
+        self.data()[self.by_x()[i] as usize]
+    }
+}
+
+/// Points of the upper line.
+impl SeriesPoints for AreaSeries {
+    fn len(&self) -> usize {
+        self.data().len()
+    }
+
+    fn point(&self, i: usize) -> Point2d {
+        self.data()[i]
+    }
+}
+
+impl SeriesPoints for BarSeries {
+    fn len(&self) -> usize {
+        self.data().len()
+    }
+
+    fn point(&self, i: usize) -> Point2d {
+        self.data()[i]
+    }
+}
+
+impl SeriesPoints for OhlcSeries {
+    fn len(&self) -> usize {
+        self.data().len()
+    }
+
+    fn point(&self, i: usize) -> Point2d {
+        self.data().point(i)
+    }
+
+    fn ohlc(&self, i: usize) -> Option<Ohlc> {
+        Some(self.data()[i])
+    }
+}
+
+/// How the cursor snaps to data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnapMode {
+    /// follows the cursor
+    None,
+    /// point with the closest x
+    X,
+    /// closest point in pixels
+    Euclidean,
+}
+
+impl Default for SnapMode {
+    fn default() -> Self {
+        SnapMode::None
+    }
+}
+
+/// Index of the point with x closest to `x`, binary search.
+pub fn nearest_x<P: SeriesPoints + ?Sized>(points: &P, x: f32) -> Option<usize> {
+    if points.is_empty() {
+        return None;
+    }
+    // first point at or after x
+    let (mut lo, mut hi) = (0, points.len());
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if points.point(mid).x < x {
+            lo = mid + 1;
+        } else {
+            hi = mid;
+        }
+    }
+    if lo == points.len() {
+        return Some(lo - 1);
+    }
+    if lo > 0 && x - points.point(lo - 1).x <= points.point(lo).x - x {
+        return Some(lo - 1);
+    }
+    Some(lo)
+}
+
+/// Index of the point closest to `target` in pixels that is at most `max_distance` pixels away,
+/// `to_pixels` converts data into pixels and must keep the order of x.
+/// Starts at the closest x and walks outwards until x alone is farther than the best point or
+/// `max_distance`.
+pub fn nearest<P: SeriesPoints + ?Sized>(
+    points: &P,
+    target: Point2d,
+    max_distance: f32,
+    to_pixels: impl Fn(Point2d) -> Vec2,
+) -> Option<usize> {
+    let start = nearest_x(points, target.x)?;
+    let target = to_pixels(target);
+    let mut best = (None, max_distance);
+    let mut walk = |range: &mut dyn Iterator<Item = usize>| {
+        for i in range {
+            let pixels = to_pixels(points.point(i));
+            if (pixels.x - target.x).abs() > best.1 {
+                break;
+            }
+            let d = nalgebra_glm::distance(&pixels, &target);
+            if d < best.1 || best.0.is_none() && d <= best.1 {
+                best = (Some(i), d);
+            }
+        }
+    };
+    walk(&mut (start..points.len()));
+    walk(&mut (0..start).rev());
+    best.0
+}
+
+/// Snaps `target` to a point of `points`, returns the index of the point.
+pub fn snap<P: SeriesPoints + ?Sized>(
+    points: &P,
+    mode: SnapMode,
+    target: Point2d,
+    to_pixels: impl Fn(Point2d) -> Vec2,
+) -> Option<usize> {
+    match mode {
+        SnapMode::None => None,
+        SnapMode::X => nearest_x(points, target.x),
+        SnapMode::Euclidean => nearest(points, target, f32::INFINITY, to_pixels),
+    }
+}
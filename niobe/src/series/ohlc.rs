@@ -0,0 +1,332 @@
+use nalgebra_glm::Vec2;
+use niobe_core::buffer::Buffer;
+use niobe_core::pipelines::mesh::{MeshBindGroup, MeshDrawer, MeshUniform, RectInstance};
+use niobe_core::pipelines::{AxisScale, BlendMode};
+use niobe_core::{Mesh2d, Point2d};
+use rgb::RGBA;
+use std::cell::Cell;
+use std::rc::Rc;
+use wgpu::util::RenderEncoder;
+use wgpu::{BufferUsages, Device, Queue};
+
+const UP: usize = 0;
+const DOWN: usize = 1;
+
+// uniform ids
+const UP_WICK: u32 = 0;
+const DOWN_WICK: u32 = 1;
+const UP_BODY: u32 = 2;
+const DOWN_BODY: u32 = 3;
+const HOLLOW: u32 = 4;
+const UP_TICK: u32 = 5;
+const DOWN_TICK: u32 = 6;
+const N_UNIFORMS: usize = 7;
+
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Ohlc {
+    pub x: f32,
+    pub open: f32,
+    pub high: f32,
+    pub low: f32,
+    pub close: f32,
+}
+
+impl Ohlc {
+    /// Candle is up if it closed at or above open.
+    pub fn is_up(&self) -> bool {
+        self.close >= self.open
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum OhlcStyle {
+    /// Body from open to close with a wick from low to high.
+    /// Hollow candles draw up candles as outlines.
+    Candlestick { hollow: bool },
+    /// Vertical line from low to high with open tick on the left and close tick on the right.
+    Bar,
+}
+
+pub struct OhlcSeriesConfig {
+    pub style: OhlcStyle,
+    pub up_color: RGBA<f32>,
+    pub down_color: RGBA<f32>,
+    /// fill color of hollow candles, usually background color of the view
+    pub hollow_color: RGBA<f32>,
+    /// width of a body or bar ticks in data units
+    pub bar_width: f32,
+    /// width of a wick or a bar in pixels
+    pub wick_width: u32,
+    /// border width of hollow candles and thickness of bar ticks in pixels
+    pub border_width: u32,
+    /// blend mode of the pipeline the series is drawn with, see `BlendCache`
+    pub blend: BlendMode,
+}
+
+/// Rectangles of candles that moved in the same direction, one instance per candle.
+struct Instances {
+    wicks: Buffer<RectInstance>,
+    bodies: Buffer<RectInstance>,
+    opens: Buffer<RectInstance>,
+    closes: Buffer<RectInstance>,
+}
+
+impl Instances {
+    fn new(device: &Device) -> Self {
+        let buffer = || Buffer::with_capacity(device, BufferUsages::VERTEX, 1024);
+        Self {
+            wicks: buffer(),
+            bodies: buffer(),
+            opens: buffer(),
+            closes: buffer(),
+        }
+    }
+
+    /// Appends candles with one write per buffer.
+    fn extend(&mut self, device: &Device, queue: &Queue, rects: &[[RectInstance; 4]]) {
+        let part = |i: usize| rects.iter().map(|x| x[i]).collect::<Vec<_>>();
+        self.wicks.extend(device, queue, &part(0));
+        self.bodies.extend(device, queue, &part(1));
+        self.opens.extend(device, queue, &part(2));
+        self.closes.extend(device, queue, &part(3));
+    }
+
+    fn write_last(&self, queue: &Queue, rects: &[RectInstance; 4]) {
+        let last = self.wicks.len() as usize - 1;
+        self.wicks.write(queue, last, &rects[0..1]);
+        self.bodies.write(queue, last, &rects[1..2]);
+        self.opens.write(queue, last, &rects[2..3]);
+        self.closes.write(queue, last, &rects[3..4]);
+    }
+
+    fn pop(&mut self) {
+        let len = self.wicks.len() - 1;
+        self.wicks.truncate(len);
+        self.bodies.truncate(len);
+        self.opens.truncate(len);
+        self.closes.truncate(len);
+    }
+
+    fn clear(&mut self) {
+        self.wicks.clear();
+        self.bodies.clear();
+        self.opens.clear();
+        self.closes.clear();
+    }
+}
+
+/// Candlestick or OHLC bar series, each candle is one instance of a quad for every part of it.
+/// Candles are split by direction so that up and down candles can be drawn with their own color
+/// in one draw call each.
+pub struct OhlcSeries {
+    style: OhlcStyle,
+    bar_width: f32,
+    wick_width: u32,
+    border_width: u32,
+    data: Vec<Ohlc>,
+    visible: Rc<Cell<bool>>,
+    blend: BlendMode,
+    instances: [Instances; 2],
+    quad: Mesh2d<u16>,
+    mesh_ubd: Vec<MeshUniform>,
+    mesh_ubo: Buffer<MeshUniform>,
+    mesh_group: MeshBindGroup,
+}
+
+impl OhlcSeries {
+    pub fn new(device: &Device, config: &OhlcSeriesConfig) -> Self {
+        let uniform = |color| MeshUniform {
+            color,
+            scale: Vec2::new(1., 1.),
+            translate: Vec2::new(0., 0.),
+            mesh_scale: Vec2::new(0., 0.),
+            log_base: Vec2::new(0., 0.),
+        };
+        let mut mesh_ubd = vec![uniform(config.up_color); N_UNIFORMS];
+        mesh_ubd[DOWN_WICK as usize] = uniform(config.down_color);
+        mesh_ubd[DOWN_BODY as usize] = uniform(config.down_color);
+        mesh_ubd[DOWN_TICK as usize] = uniform(config.down_color);
+        mesh_ubd[HOLLOW as usize] = uniform(config.hollow_color);
+        let mesh_ubo = Buffer::new(
+            &device,
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            &mesh_ubd,
+        );
+        let mesh_group = MeshBindGroup::new(&device, mesh_ubo.slice(..));
+        Self {
+            style: config.style,
+            bar_width: config.bar_width,
+            wick_width: config.wick_width,
+            border_width: config.border_width,
+            data: Vec::new(),
+            visible: Rc::new(Cell::new(true)),
+            blend: config.blend,
+            instances: [Instances::new(device), Instances::new(device)],
+            quad: Mesh2d::quad(device),
+            mesh_ubd,
+            mesh_ubo,
+            mesh_group,
+        }
+    }
+
+    /// Blend mode of the pipeline this series must be drawn with.
+    pub fn blend(&self) -> BlendMode {
+        self.blend
+    }
+
+    /// Series is not drawn while the flag is false, eg. it's toggled by `Legend`.
+    pub fn visibility(&self) -> Rc<Cell<bool>> {
+        self.visible.clone()
+    }
+
+    pub fn data(&self) -> &[Ohlc] {
+        &self.data
+    }
+
+    /// Appends candles, they must be sorted by x.
+    pub fn extend(&mut self, device: &Device, queue: &Queue, data: &[Ohlc]) {
+        let mut rects = [Vec::new(), Vec::new()];
+        for ohlc in data {
+            rects[direction(ohlc)].push(self.rects(ohlc));
+        }
+        for (instances, rects) in self.instances.iter_mut().zip(&rects) {
+            if !rects.is_empty() {
+                instances.extend(device, queue, rects);
+            }
+        }
+        self.data.extend_from_slice(data);
+    }
+
+    pub fn push(&mut self, device: &Device, queue: &Queue, ohlc: Ohlc) {
+        self.extend(device, queue, &[ohlc]);
+    }
+
+    /// Replaces the last candle, used for live data where the last candle is still forming.
+    pub fn set_last(&mut self, device: &Device, queue: &Queue, ohlc: Ohlc) {
+        let last = match self.data.last_mut() {
+            Some(last) => last,
+            None => return self.push(device, queue, ohlc),
+        };
+        let prev_direction = direction(last);
+        *last = ohlc;
+        let direction = direction(&ohlc);
+        let rects = self.rects(&ohlc);
+        if prev_direction == direction {
+            self.instances[direction].write_last(queue, &rects);
+        } else {
+            // last candle is always the last instance of its direction
+            self.instances[prev_direction].pop();
+            self.instances[direction].extend(device, queue, &[rects]);
+        }
+    }
+
+    pub fn set_data(&mut self, device: &Device, queue: &Queue, data: &[Ohlc]) {
+        self.data.clear();
+        self.instances.iter_mut().for_each(Instances::clear);
+        self.extend(device, queue, data);
+    }
+
+    /// Log axes transform points in the shader, takes effect on the next `set_transform`.
+    pub fn set_axis_scales(&mut self, x: AxisScale, y: AxisScale) {
+        for uniform in &mut self.mesh_ubd {
+            uniform.log_base = AxisScale::log_bases(x, y);
+        }
+    }
+
+    /// Updates data to clip space transform, `pixel_scale` is size of a pixel in clip space.
+    pub fn set_transform(
+        &mut self,
+        queue: &Queue,
+        scale: Vec2,
+        translate: Vec2,
+        pixel_scale: Vec2,
+    ) {
+        let wick = self.wick_width as f32 * pixel_scale.x;
+        let border = self.border_width as f32 * pixel_scale;
+        for (i, uniform) in self.mesh_ubd.iter_mut().enumerate() {
+            uniform.scale = scale;
+            uniform.translate = translate;
+            uniform.mesh_scale = match i as u32 {
+                UP_WICK | DOWN_WICK => Vec2::new(wick, 0.),
+                // keep dojis visible
+                UP_BODY | DOWN_BODY => Vec2::new(0., pixel_scale.y),
+                HOLLOW => Vec2::new(-2. * border.x, pixel_scale.y - 2. * border.y),
+                UP_TICK | DOWN_TICK => Vec2::new(0., border.y),
+                _ => unreachable!(),
+            };
+        }
+        self.mesh_ubo.write_sliced(queue, .., &self.mesh_ubd);
+    }
+
+    pub fn draw_mesh<'s, 'e, E: RenderEncoder<'s>>(&'s self, drawer: &mut MeshDrawer<'e, E>) {
+        if !self.visible.get() {
+            return;
+        }
+        drawer
+            .set_vertices(self.quad.vertices.slice(..))
+            .set_indices(self.quad.indices.slice(..));
+        let [up, down] = &self.instances;
+        match self.style {
+            OhlcStyle::Candlestick { hollow } => {
+                drawer
+                    .set_bind_group(&self.mesh_group, UP_WICK)
+                    .draw_rects(up.wicks.slice(..))
+                    .set_bind_group(&self.mesh_group, DOWN_WICK)
+                    .draw_rects(down.wicks.slice(..))
+                    .set_bind_group(&self.mesh_group, UP_BODY)
+                    .draw_rects(up.bodies.slice(..))
+                    .set_bind_group(&self.mesh_group, DOWN_BODY)
+                    .draw_rects(down.bodies.slice(..));
+                if hollow {
+                    drawer
+                        .set_bind_group(&self.mesh_group, HOLLOW)
+                        .draw_rects(up.bodies.slice(..));
+                }
+            }
+            OhlcStyle::Bar => {
+                drawer
+                    .set_bind_group(&self.mesh_group, UP_WICK)
+                    .draw_rects(up.wicks.slice(..))
+                    .set_bind_group(&self.mesh_group, DOWN_WICK)
+                    .draw_rects(down.wicks.slice(..))
+                    .set_bind_group(&self.mesh_group, UP_TICK)
+                    .draw_rects(up.opens.slice(..))
+                    .draw_rects(up.closes.slice(..))
+                    .set_bind_group(&self.mesh_group, DOWN_TICK)
+                    .draw_rects(down.opens.slice(..))
+                    .draw_rects(down.closes.slice(..));
+            }
+        }
+    }
+
+    /// wick, body, open tick and close tick
+    fn rects(&self, ohlc: &Ohlc) -> [RectInstance; 4] {
+        let w = self.bar_width;
+        [
+            RectInstance {
+                pos: Point2d::new(ohlc.x, (ohlc.high + ohlc.low) / 2.),
+                size: Vec2::new(0., ohlc.high - ohlc.low),
+            },
+            RectInstance {
+                pos: Point2d::new(ohlc.x, (ohlc.open + ohlc.close) / 2.),
+                size: Vec2::new(w, (ohlc.close - ohlc.open).abs()),
+            },
+            RectInstance {
+                pos: Point2d::new(ohlc.x - w / 4., ohlc.open),
+                size: Vec2::new(w / 2., 0.),
+            },
+            RectInstance {
+                pos: Point2d::new(ohlc.x + w / 4., ohlc.close),
+                size: Vec2::new(w / 2., 0.),
+            },
+        ]
+    }
+}
+
+fn direction(ohlc: &Ohlc) -> usize {
+    if ohlc.is_up() {
+        UP
+    } else {
+        DOWN
+    }
+}
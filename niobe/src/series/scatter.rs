@@ -0,0 +1,202 @@
+use nalgebra_glm::Vec2;
+use niobe_core::buffer::Buffer;
+use niobe_core::pipelines::mesh::{InstanceLayout, MeshBindGroup, MeshDrawer, MeshUniform};
+use niobe_core::pipelines::{AxisScale, BlendMode};
+use niobe_core::{MarkerShape, Mesh2d, Point2d};
+use rgb::RGBA;
+use std::cell::Cell;
+use std::rc::Rc;
+use wgpu::util::RenderEncoder;
+use wgpu::{BufferUsages, Device, Queue};
+
+pub struct ScatterSeriesConfig {
+    pub shape: MarkerShape,
+    /// color of every marker, ignored if `per_point_color` is set
+    pub color: RGBA<f32>,
+    /// marker size in pixels, ignored if `per_point_size` is set
+    pub size: f32,
+    /// every point has its own color passed to `extend`
+    pub per_point_color: bool,
+    /// every point has its own size in pixels passed to `extend`
+    pub per_point_size: bool,
+    /// blend mode of the pipeline the series is drawn with, see `BlendCache`
+    pub blend: BlendMode,
+}
+
+/// Scatter series, each point is an instance of a marker mesh.
+/// Markers are sized in pixels so they keep their size when zooming.
+pub struct ScatterSeries {
+    /// positions kept on the cpu for lookups under the cursor
+    data: Vec<Point2d>,
+    /// indices of `data` sorted by x, points with NaN x are left out
+    by_x: Vec<u32>,
+    visible: Rc<Cell<bool>>,
+    blend: BlendMode,
+    size: f32,
+    positions: Buffer<Point2d>,
+    colors: Option<Buffer<RGBA<f32>>>,
+    sizes: Option<Buffer<f32>>,
+    marker: Mesh2d<u16>,
+    mesh_ubd: [MeshUniform; 1],
+    mesh_ubo: Buffer<MeshUniform>,
+    mesh_group: MeshBindGroup,
+}
+
+impl ScatterSeries {
+    pub fn new(device: &Device, config: &ScatterSeriesConfig) -> Self {
+        let mesh_ubd = [MeshUniform {
+            color: config.color,
+            scale: Vec2::new(1., 1.),
+            translate: Vec2::new(0., 0.),
+            mesh_scale: Vec2::new(0., 0.),
+            log_base: Vec2::new(0., 0.),
+        }];
+        let mesh_ubo = Buffer::new(
+            &device,
+            BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+            &mesh_ubd,
+        );
+        let mesh_group = MeshBindGroup::new(&device, mesh_ubo.slice(..));
+        Self {
+            data: Vec::new(),
+            by_x: Vec::new(),
+            visible: Rc::new(Cell::new(true)),
+            blend: config.blend,
+            size: config.size,
+            positions: Buffer::with_capacity(device, BufferUsages::VERTEX, 1024),
+            colors: config
+                .per_point_color
+                .then(|| Buffer::with_capacity(device, BufferUsages::VERTEX, 1024)),
+            sizes: config
+                .per_point_size
+                .then(|| Buffer::with_capacity(device, BufferUsages::VERTEX, 1024)),
+            marker: Mesh2d::marker(device, config.shape),
+            mesh_ubd,
+            mesh_ubo,
+            mesh_group,
+        }
+    }
+
+    /// Layout of a `MeshPipeline` that this series must be drawn with.
+    pub fn instance_layout(&self) -> InstanceLayout {
+        InstanceLayout::Marker {
+            color: self.colors.is_some(),
+            size: self.sizes.is_some(),
+        }
+    }
+
+    /// Blend mode of the pipeline this series must be drawn with.
+    pub fn blend(&self) -> BlendMode {
+        self.blend
+    }
+
+    /// Series is not drawn while the flag is false, eg. it's toggled by `Legend`.
+    pub fn visibility(&self) -> Rc<Cell<bool>> {
+        self.visible.clone()
+    }
+
+    /// Points in the order they were added.
+    pub fn data(&self) -> &[Point2d] {
+        &self.data
+    }
+
+    /// Indices of `data` sorted by x, for lookups under the cursor.
+    pub fn by_x(&self) -> &[u32] {
+        &self.by_x
+    }
+
+    pub fn len(&self) -> u32 {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Appends points, `colors` and `sizes` are required if the series was created with per point
+    /// colors or sizes and must have the same length as `points`.
+    pub fn extend(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        points: &[Point2d],
+        colors: Option<&[RGBA<f32>]>,
+        sizes: Option<&[f32]>,
+    ) {
+        self.positions.extend(device, queue, points);
+        let start = self.data.len() as u32;
+        self.data.extend_from_slice(points);
+        let data = &self.data;
+        let x = |i: &u32| data[*i as usize].x;
+        self.by_x
+            .extend((start..data.len() as u32).filter(|i| !x(i).is_nan()));
+        // stable sort is close to linear when most of the indices are already sorted
+        self.by_x.sort_by(|a, b| x(a).partial_cmp(&x(b)).unwrap());
+        if let Some(buffer) = &mut self.colors {
+            let colors = colors.expect("series has per point colors");
+            debug_assert_eq!(points.len(), colors.len());
+            buffer.extend(device, queue, colors);
+        }
+        if let Some(buffer) = &mut self.sizes {
+            let sizes = sizes.expect("series has per point sizes");
+            debug_assert_eq!(points.len(), sizes.len());
+            buffer.extend(device, queue, sizes);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.data.clear();
+        self.by_x.clear();
+        if let Some(colors) = &mut self.colors {
+            colors.clear();
+        }
+        if let Some(sizes) = &mut self.sizes {
+            sizes.clear();
+        }
+    }
+
+    /// Sets size in pixels of markers, has no effect with per point sizes.
+    pub fn set_size(&mut self, size: f32) {
+        self.size = size;
+    }
+
+    /// Log axes transform points in the shader, takes effect on the next `set_transform`.
+    pub fn set_axis_scales(&mut self, x: AxisScale, y: AxisScale) {
+        self.mesh_ubd[0].log_base = AxisScale::log_bases(x, y);
+    }
+
+    /// Updates data to clip space transform, `pixel_scale` is size of a pixel in clip space.
+    pub fn set_transform(
+        &mut self,
+        queue: &Queue,
+        scale: Vec2,
+        translate: Vec2,
+        pixel_scale: Vec2,
+    ) {
+        self.mesh_ubd[0].scale = scale;
+        self.mesh_ubd[0].translate = translate;
+        // per point sizes are multiplied in the shader
+        self.mesh_ubd[0].mesh_scale = match self.sizes {
+            Some(_) => pixel_scale,
+            None => pixel_scale * self.size,
+        };
+        self.mesh_ubo.write_sliced(queue, .., &self.mesh_ubd);
+    }
+
+    /// `drawer` must be created from a pipeline with `instance_layout`.
+    pub fn draw_mesh<'s, 'e, E: RenderEncoder<'s>>(&'s self, drawer: &mut MeshDrawer<'e, E>) {
+        if !self.visible.get() {
+            return;
+        }
+        drawer
+            .set_vertices(self.marker.vertices.slice(..))
+            .set_indices(self.marker.indices.slice(..))
+            .set_bind_group(&self.mesh_group, 0)
+            .draw_markers(
+                self.positions.slice(..),
+                self.colors.as_ref().map(|x| x.slice(..)),
+                self.sizes.as_ref().map(|x| x.slice(..)),
+            );
+    }
+}